@@ -0,0 +1,263 @@
+//! Bitwise-parallel neighbor counting, used to compute the next generation.
+//!
+//! Each row is packed into `u64` words with a bit per cell, and shifted left
+//! and right by a column to line each cell up with its neighbors on either
+//! side. The eight neighbors of 64 cells at a time are then added together
+//! with bitwise full adders, into four words holding the binary digits of
+//! each cell's count.
+//!
+//! The adders work on any `Word` of cells, so that the `simd` feature can
+//! count the neighbors of several `u64`s of cells in each operation.
+
+use std::ops::{BitAnd, BitOr, BitXor, Not};
+
+use fixedbitset::{Block, FixedBitSet};
+
+use crate::Universe;
+
+/// The number of cells in a block of the bitset.
+const BLOCK_BITS: usize = Block::BITS as usize;
+
+impl Universe {
+    /// Write the cells of the next generation into `next`, which must be the
+    /// same length as the current cells.
+    pub(crate) fn write_next_cells_bitwise(&self, next: &mut FixedBitSet) {
+        self.write_next_cells_in::<u64>(next);
+    }
+
+    /// Write the cells of the next generation into `next` like
+    /// `write_next_cells_bitwise`, adding up neighbors in words of type `W`.
+    pub(crate) fn write_next_cells_in<W: Word>(&self, next: &mut FixedBitSet) {
+        // Narrower universes have fewer than eight distinct neighbors for each
+        // cell, which the neighbor table accounts for.
+        if self.width < 3 {
+            for row in 0..self.height {
+                for col in 0..self.width {
+                    next.set(self.get_index(row, col), self.next_cell(row, col));
+                }
+            }
+            return;
+        }
+
+        let width = self.width as usize;
+        let mut kernel = RowKernel::<W>::new(self);
+        for row in 0..self.height as usize {
+            write_bits(
+                next.as_mut_slice(),
+                row * width,
+                width,
+                kernel.next_row(row),
+            );
+        }
+    }
+}
+
+/// A group of `u64`s of cells, with a bit per cell, that neighbors are added
+/// up in.
+pub(crate) trait Word:
+    Copy + BitAnd<Output = Self> + BitOr<Output = Self> + BitXor<Output = Self> + Not<Output = Self>
+{
+    /// The number of `u64`s in the word.
+    const LANES: usize;
+    /// A word of dead cells.
+    const DEAD: Self;
+
+    /// Read a word from exactly `LANES` `u64`s.
+    fn load(words: &[u64]) -> Self;
+
+    /// Write a word into exactly `LANES` `u64`s.
+    fn store(self, words: &mut [u64]);
+}
+
+impl Word for u64 {
+    const LANES: usize = 1;
+    const DEAD: u64 = 0;
+
+    fn load(words: &[u64]) -> u64 {
+        words[0]
+    }
+
+    fn store(self, words: &mut [u64]) {
+        words[0] = self;
+    }
+}
+
+/// Computes the next generation one row at a time, reusing its buffers from
+/// row to row.
+///
+/// The universe must be at least 3 cells wide.
+pub(crate) struct RowKernel<'a, W: Word> {
+    universe: &'a Universe,
+    survives: [bool; 9],
+    is_born: [bool; 9],
+    /// The number of `u64`s in a row, before padding to a whole number of
+    /// words.
+    row_len: usize,
+    rows: [Vec<u64>; 3],
+    shifted: [Vec<u64>; 2],
+    digits: Vec<[W; 4]>,
+    next_row: Vec<u64>,
+}
+
+impl<'a, W: Word> RowKernel<'a, W> {
+    pub(crate) fn new(universe: &'a Universe) -> RowKernel<'a, W> {
+        let row_len = (universe.width as usize).div_ceil(64);
+        let words = row_len.div_ceil(W::LANES);
+        let padded_len = words * W::LANES;
+        RowKernel {
+            universe,
+            survives: std::array::from_fn(|n| universe.rule.survives(n as u8)),
+            is_born: std::array::from_fn(|n| universe.rule.is_born(n as u8)),
+            row_len,
+            rows: std::array::from_fn(|_| vec![0; padded_len]),
+            shifted: std::array::from_fn(|_| vec![0; padded_len]),
+            digits: vec![[W::DEAD; 4]; words],
+            next_row: vec![0; padded_len],
+        }
+    }
+
+    /// Get the cells of the given row in the next generation, as words of a
+    /// bit per cell.
+    ///
+    /// Any bits past the width of the universe in the last word are
+    /// meaningless.
+    pub(crate) fn next_row(&mut self, row: usize) -> &[u64] {
+        // The padding past the end of each row is never written, so it
+        // stays dead.
+        let row_len = self.row_len;
+        let lanes = |word: usize| word * W::LANES..(word + 1) * W::LANES;
+
+        self.digits.fill([W::DEAD; 4]);
+        let neighbor_rows = self.universe.neighbor_rows[row];
+        for (delta_row, row_start) in neighbor_rows.into_iter().enumerate() {
+            let cells = &mut self.rows[delta_row];
+            read_row_words(self.universe, row_start, &mut cells[..row_len]);
+            shift_row_words(self.universe, &cells[..row_len], &mut self.shifted);
+
+            let [left, right] = &self.shifted;
+            for (word, digits) in self.digits.iter_mut().enumerate() {
+                add(digits, W::load(&left[lanes(word)]));
+                add(digits, W::load(&right[lanes(word)]));
+                if delta_row != 1 {
+                    add(digits, W::load(&cells[lanes(word)]));
+                }
+            }
+        }
+
+        let on = &self.rows[1];
+        for (word, digits) in self.digits.iter().enumerate() {
+            let on = W::load(&on[lanes(word)]);
+            let mut alive = W::DEAD;
+            for count in 0..=8 {
+                let has_count = (0..4).fold(!W::DEAD, |mask, digit| {
+                    mask & match count >> digit & 1 {
+                        1 => digits[digit],
+                        _ => !digits[digit],
+                    }
+                });
+                if self.survives[count] {
+                    alive = alive | has_count & on;
+                }
+                if self.is_born[count] {
+                    alive = alive | has_count & !on;
+                }
+            }
+            alive.store(&mut self.next_row[lanes(word)]);
+        }
+
+        &self.next_row[..row_len]
+    }
+}
+
+/// Read the row starting at the given index as a bit per cell, or all dead
+/// cells for a row outside the universe.
+fn read_row_words(universe: &Universe, row_start: Option<u32>, cells: &mut [u64]) {
+    let Some(row_start) = row_start else {
+        cells.fill(0);
+        return;
+    };
+
+    let width = universe.width as usize;
+    let blocks = universe.cells.as_slice();
+    for (word, cells) in cells.iter_mut().enumerate() {
+        let start = word * 64;
+        *cells = read_bits(blocks, row_start as usize + start, (width - start).min(64));
+    }
+}
+
+/// Shift a row of cells so that each cell lines up with its neighbor on the
+/// left and then on the right, taking the neighbors of the first and last
+/// columns from the neighbor table.
+///
+/// Only as many `u64`s as there are in `cells` are written.
+fn shift_row_words(universe: &Universe, cells: &[u64], shifted: &mut [Vec<u64>; 2]) {
+    let width = universe.width as usize;
+    let cell = |col: Option<u32>| match col {
+        Some(col) => cells[col as usize / 64] >> (col % 64) & 1,
+        None => 0,
+    };
+
+    let [left, right] = shifted;
+    for word in 0..cells.len() {
+        let previous = word.checked_sub(1).map_or(0, |word| cells[word] >> 63);
+        let following = cells.get(word + 1).map_or(0, |cells| cells << 63);
+        left[word] = cells[word] << 1 | previous;
+        right[word] = cells[word] >> 1 | following;
+    }
+
+    left[0] |= cell(universe.neighbor_columns[0][0]);
+    let last = width - 1;
+    right[last / 64] |= cell(universe.neighbor_columns[last][2]) << (last % 64);
+}
+
+/// Add a word of one bit per cell to the binary digits of each cell's
+/// count.
+fn add<W: Word>(digits: &mut [W; 4], mut carry: W) {
+    for digit in digits.iter_mut() {
+        let next_carry = *digit & carry;
+        *digit = *digit ^ carry;
+        carry = next_carry;
+    }
+}
+
+/// Read up to 64 bits of a bitset's blocks, starting at the given bit.
+fn read_bits(blocks: &[Block], start: usize, len: usize) -> u64 {
+    let mut bits = 0;
+    let mut read = 0;
+    while read < len {
+        let (block, offset) = ((start + read) / BLOCK_BITS, (start + read) % BLOCK_BITS);
+        let count = (BLOCK_BITS - offset).min(len - read);
+        bits |= ((blocks[block] >> offset) as u64 & low_bits(count)) << read;
+        read += count;
+    }
+    bits
+}
+
+/// Write `len` bits from words of 64 bits into a bitset's blocks, starting
+/// at the given bit and leaving every other bit unchanged.
+pub(crate) fn write_bits(blocks: &mut [Block], start: usize, len: usize, words: &[u64]) {
+    let mut written = 0;
+    while written < len {
+        let (block, offset) = (
+            (start + written) / BLOCK_BITS,
+            (start + written) % BLOCK_BITS,
+        );
+        let (word, word_offset) = (written / 64, written % 64);
+        let count = (BLOCK_BITS - offset)
+            .min(64 - word_offset)
+            .min(len - written);
+
+        let mask = (low_bits(count) as Block) << offset;
+        let bits = ((words[word] >> word_offset) as Block) << offset;
+        blocks[block] = blocks[block] & !mask | bits & mask;
+        written += count;
+    }
+}
+
+/// Get a word with the lowest `count` bits set.
+fn low_bits(count: usize) -> u64 {
+    match count {
+        64 => !0,
+        count => (1 << count) - 1,
+    }
+}
@@ -1,5 +1,19 @@
+mod bitwise;
+mod life106;
+#[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
+mod parallel;
+mod plaintext;
+#[cfg(feature = "png")]
+mod png;
+mod rle;
+#[cfg(feature = "serde")]
+mod serialization;
+#[cfg(feature = "simd")]
+mod simd;
 mod utils;
 
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
 use std::str::FromStr;
 
 use wasm_bindgen::prelude::*;
@@ -18,26 +32,306 @@ use js_sys::Math;
 extern crate fixedbitset;
 use fixedbitset::FixedBitSet;
 
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+
 #[wasm_bindgen]
-#[derive(Default)]
 pub struct Universe {
     width: u32,
     height: u32,
     cells: FixedBitSet,
+    boundary: Boundary,
+    rule: Rule,
+    generation: u64,
+    /// Earlier cells, each with the generation they were saved at.
+    history: VecDeque<(FixedBitSet, u64)>,
+    history_limit: usize,
+    /// Undone cells, each with the generation they were undone at.
+    redo_stack: Vec<(FixedBitSet, u64)>,
+    custom_patterns: HashMap<String, Vec<(i32, i32)>>,
+    track_ages: bool,
+    ages: Vec<u16>,
+    /// The color of each cell for the immigration variant, 1 or 2 for live
+    /// cells, or empty if no colors have been set.
+    colors: Vec<u8>,
+    changed: Vec<u32>,
+    /// The number of cells born and the number that died in the last tick.
+    last_births: u32,
+    last_deaths: u32,
+    scratch: FixedBitSet,
+    neighbor_rows: Vec<[Option<u32>; 3]>,
+    neighbor_columns: Vec<[Option<u32>; 3]>,
+    initial: Option<FixedBitSet>,
+    /// The population after each of the most recent generations.
+    pop_history: VecDeque<u32>,
+    pop_history_limit: usize,
+    /// The largest width and height the universe grows to with the
+    /// `AutoGrow` boundary.
+    auto_grow_limit: (u32, u32),
+}
+
+/// The cells and generation of a universe at one point in time, saved by
+/// `Universe::snapshot` to be restored later.
+#[wasm_bindgen]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Snapshot {
+    width: u32,
+    height: u32,
+    cells: FixedBitSet,
+    generation: u64,
+}
+
+/// The number of earlier states kept for `Universe::undo`.
+const DEFAULT_HISTORY_LIMIT: usize = 100;
+
+/// The number of populations kept for `Universe::population_history`.
+const DEFAULT_POPULATION_HISTORY_LIMIT: usize = 1000;
+
+/// The number of rows or columns added past an edge each time the universe
+/// grows with the `AutoGrow` boundary.
+const AUTO_GROW_MARGIN: u32 = 16;
+
+/// The largest width and height the universe grows to with the `AutoGrow`
+/// boundary by default.
+const DEFAULT_AUTO_GROW_LIMIT: u32 = 1024;
+
+/// The number of recent states `Universe::run_until_cycle` looks for
+/// repeats among by default.
+const DEFAULT_CYCLE_WINDOW: u32 = 64;
+
+/// How neighbors are found for cells on the edges of the universe.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Boundary {
+    /// Edges wrap around to the opposite side.
+    #[default]
+    Toroidal,
+    /// Cells outside the universe are permanently dead.
+    Dead,
+    /// Edges mirror back onto the universe, so that the row above the top
+    /// edge is a copy of the top row, and so on.
+    Reflective,
+    /// The left and right edges wrap around to each other, while cells
+    /// above the top and below the bottom are permanently dead.
+    Cylinder,
+    /// Cells outside the universe are dead, but the universe grows past any
+    /// edge that a live cell reaches before each generation, up to the size
+    /// set by `Universe::set_auto_grow_limit`.
+    AutoGrow,
+}
+
+/// How coordinates past the edges of a universe are handled along one axis.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Edge {
+    Wrap,
+    Dead,
+    Reflect,
+}
+
+impl Boundary {
+    /// Get how the edges are handled along the rows and along the columns.
+    fn edges(self) -> (Edge, Edge) {
+        match self {
+            Boundary::Toroidal => (Edge::Wrap, Edge::Wrap),
+            Boundary::Dead => (Edge::Dead, Edge::Dead),
+            Boundary::Reflective => (Edge::Reflect, Edge::Reflect),
+            Boundary::Cylinder => (Edge::Dead, Edge::Wrap),
+            Boundary::AutoGrow => (Edge::Dead, Edge::Dead),
+        }
+    }
 }
 
+#[derive(Clone, Copy)]
 enum Pattern {
     Glider,
     Pulsar,
+    Blinker,
+    Toad,
+    Beacon,
+    Pentadecathlon,
+    Lwss,
+    Mwss,
+    Hwss,
+    GosperGliderGun,
+    Acorn,
+    Block,
+    Beehive,
+    Loaf,
 }
 
+/// Every pattern along with the names it can be deployed by, the first of
+/// which is its canonical name.
+const PATTERNS: [(&[&str], Pattern); 14] = [
+    (&["GLIDER"], Pattern::Glider),
+    (&["PULSAR"], Pattern::Pulsar),
+    (&["BLINKER"], Pattern::Blinker),
+    (&["TOAD"], Pattern::Toad),
+    (&["BEACON"], Pattern::Beacon),
+    (&["PENTADECATHLON"], Pattern::Pentadecathlon),
+    (&["LWSS"], Pattern::Lwss),
+    (&["MWSS"], Pattern::Mwss),
+    (&["HWSS"], Pattern::Hwss),
+    (&["GOSPER", "GLIDERGUN"], Pattern::GosperGliderGun),
+    (&["ACORN"], Pattern::Acorn),
+    (&["BLOCK"], Pattern::Block),
+    (&["BEEHIVE"], Pattern::Beehive),
+    (&["LOAF"], Pattern::Loaf),
+];
+
 impl FromStr for Pattern {
     type Err = ();
 
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.to_uppercase();
+        PATTERNS
+            .iter()
+            .find(|(names, _)| names.contains(&name.as_str()))
+            .map(|&(_, pattern)| pattern)
+            .ok_or(())
+    }
+}
+
+impl Pattern {
+    /// Get the offsets of the live cells from the anchor of the pattern.
+    fn offsets(&self) -> &'static [(i32, i32)] {
+        match self {
+            Pattern::Glider => &GLIDER_ALIVE_OFFSET,
+            Pattern::Pulsar => &PULSAR_ALIVE_OFFSET,
+            Pattern::Blinker => &BLINKER_ALIVE_OFFSET,
+            Pattern::Toad => &TOAD_ALIVE_OFFSET,
+            Pattern::Beacon => &BEACON_ALIVE_OFFSET,
+            Pattern::Pentadecathlon => &PENTADECATHLON_ALIVE_OFFSET,
+            Pattern::Lwss => &LWSS_ALIVE_OFFSET,
+            Pattern::Mwss => &MWSS_ALIVE_OFFSET,
+            Pattern::Hwss => &HWSS_ALIVE_OFFSET,
+            Pattern::GosperGliderGun => &GOSPER_GLIDER_GUN_ALIVE_OFFSET,
+            Pattern::Acorn => &ACORN_ALIVE_OFFSET,
+            Pattern::Block => &BLOCK_ALIVE_OFFSET,
+            Pattern::Beehive => &BEEHIVE_ALIVE_OFFSET,
+            Pattern::Loaf => &LOAF_ALIVE_OFFSET,
+        }
+    }
+}
+
+/// How a pattern is mirrored when it is deployed.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Flip {
+    None,
+    /// Mirrored left to right.
+    Horizontal,
+    /// Mirrored top to bottom.
+    Vertical,
+}
+
+/// The line of symmetry of a mirrored universe.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Axis {
+    /// The horizontal line through the middle, mirroring top to bottom.
+    Horizontal,
+    /// The vertical line through the middle, mirroring left to right.
+    Vertical,
+    /// Both lines, mirroring into all four quarters.
+    Both,
+}
+
+/// Birth and survival conditions of a Life-like cellular automaton.
+///
+/// Bit `n` of each mask is set when a cell with `n` live neighbors is born
+/// or survives, respectively.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Rule {
+    birth: u16,
+    survival: u16,
+}
+
+impl Rule {
+    fn is_born(&self, live_neighbors: u8) -> bool {
+        self.birth & (1 << live_neighbors) != 0
+    }
+
+    fn survives(&self, live_neighbors: u8) -> bool {
+        self.survival & (1 << live_neighbors) != 0
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let digits = |mask: u16| {
+            (0..=8)
+                .filter(|n| mask & (1 << n) != 0)
+                .map(|n| n.to_string())
+                .collect::<String>()
+        };
+
+        write!(f, "B{}/S{}", digits(self.birth), digits(self.survival))
+    }
+}
+
+impl Default for Rule {
+    /// Conway's Game of Life, B3/S23.
+    fn default() -> Self {
+        Rule {
+            birth: 1 << 3,
+            survival: 1 << 2 | 1 << 3,
+        }
+    }
+}
+
+impl FromStr for Rule {
+    type Err = ();
+
+    /// Parse a rulestring in B/S notation, such as `B3/S23`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        fn parse_mask(digits: &str) -> Result<u16, ()> {
+            digits
+                .chars()
+                .try_fold(0, |mask, digit| match digit.to_digit(10) {
+                    Some(n) if n <= 8 => Ok(mask | 1 << n),
+                    _ => Err(()),
+                })
+        }
+
+        let (birth, survival) = s.trim().split_once('/').ok_or(())?;
+        let birth = birth.strip_prefix(['B', 'b']).ok_or(())?;
+        let survival = survival.strip_prefix(['S', 's']).ok_or(())?;
+
+        Ok(Rule {
+            birth: parse_mask(birth)?,
+            survival: parse_mask(survival)?,
+        })
+    }
+}
+
+/// Well-known Life-like rules that can be selected by name.
+enum Ruleset {
+    Conway,
+    HighLife,
+    DayAndNight,
+    Seeds,
+}
+
+impl Ruleset {
+    fn rulestring(&self) -> &'static str {
+        match self {
+            Ruleset::Conway => "B3/S23",
+            Ruleset::HighLife => "B36/S23",
+            Ruleset::DayAndNight => "B3678/S34678",
+            Ruleset::Seeds => "B2/S",
+        }
+    }
+}
+
+impl FromStr for Ruleset {
+    type Err = ();
+
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s.to_uppercase().as_str() {
-            "GLIDER" => Ok(Pattern::Glider),
-            "PULSAR" => Ok(Pattern::Pulsar),
+            "CONWAY" => Ok(Ruleset::Conway),
+            "HIGHLIFE" => Ok(Ruleset::HighLife),
+            "DAYANDNIGHT" => Ok(Ruleset::DayAndNight),
+            "SEEDS" => Ok(Ruleset::Seeds),
             _ => Err(()),
         }
     }
@@ -99,161 +393,2220 @@ const PULSAR_ALIVE_OFFSET: [(i32, i32); 48] = [
     (1, 2),
 ];
 
+const BLINKER_ALIVE_OFFSET: [(i32, i32); 3] = [(0, -1), (0, 0), (0, 1)];
+const TOAD_ALIVE_OFFSET: [(i32, i32); 6] = [(0, 0), (0, 1), (0, 2), (1, -1), (1, 0), (1, 1)];
+const BEACON_ALIVE_OFFSET: [(i32, i32); 8] = [
+    (-2, -2),
+    (-2, -1),
+    (-1, -2),
+    (-1, -1),
+    (0, 0),
+    (0, 1),
+    (1, 0),
+    (1, 1),
+];
+const PENTADECATHLON_ALIVE_OFFSET: [(i32, i32); 12] = [
+    (-1, -2),
+    (-1, 3),
+    (0, -4),
+    (0, -3),
+    (0, -1),
+    (0, 0),
+    (0, 1),
+    (0, 2),
+    (0, 4),
+    (0, 5),
+    (1, -2),
+    (1, 3),
+];
+// Travels west.
+const LWSS_ALIVE_OFFSET: [(i32, i32); 9] = [
+    (-1, -1),
+    (-1, 2),
+    (0, -2),
+    (1, -2),
+    (1, 2),
+    (2, -2),
+    (2, -1),
+    (2, 0),
+    (2, 1),
+];
+
+// Travels west.
+const MWSS_ALIVE_OFFSET: [(i32, i32); 11] = [
+    (-2, 0),
+    (-1, -2),
+    (-1, 2),
+    (0, -3),
+    (1, -3),
+    (1, 2),
+    (2, -3),
+    (2, -2),
+    (2, -1),
+    (2, 0),
+    (2, 1),
+];
+// Travels west.
+const HWSS_ALIVE_OFFSET: [(i32, i32); 13] = [
+    (-2, 0),
+    (-2, 1),
+    (-1, -2),
+    (-1, 3),
+    (0, -3),
+    (1, -3),
+    (1, 3),
+    (2, -3),
+    (2, -2),
+    (2, -1),
+    (2, 0),
+    (2, 1),
+    (2, 2),
+];
+// Anchored at the top left corner of its bounding box, rather than its
+// center. Fires a glider to the south-east every 30 generations.
+const GOSPER_GLIDER_GUN_ALIVE_OFFSET: [(i32, i32); 36] = [
+    (0, 24),
+    (1, 22),
+    (1, 24),
+    (2, 12),
+    (2, 13),
+    (2, 20),
+    (2, 21),
+    (2, 34),
+    (2, 35),
+    (3, 11),
+    (3, 15),
+    (3, 20),
+    (3, 21),
+    (3, 34),
+    (3, 35),
+    (4, 0),
+    (4, 1),
+    (4, 10),
+    (4, 16),
+    (4, 20),
+    (4, 21),
+    (5, 0),
+    (5, 1),
+    (5, 10),
+    (5, 14),
+    (5, 16),
+    (5, 17),
+    (5, 22),
+    (5, 24),
+    (6, 10),
+    (6, 16),
+    (6, 24),
+    (7, 11),
+    (7, 15),
+    (8, 12),
+    (8, 13),
+];
+
+// A methuselah, taking 5206 generations to stabilize.
+const ACORN_ALIVE_OFFSET: [(i32, i32); 7] =
+    [(-1, -2), (0, 0), (1, -3), (1, -2), (1, 1), (1, 2), (1, 3)];
+const BLOCK_ALIVE_OFFSET: [(i32, i32); 4] = [(0, 0), (0, 1), (1, 0), (1, 1)];
+const BEEHIVE_ALIVE_OFFSET: [(i32, i32); 6] = [(-1, 0), (-1, 1), (0, -1), (0, 2), (1, 0), (1, 1)];
+const LOAF_ALIVE_OFFSET: [(i32, i32); 7] =
+    [(-1, 0), (-1, 1), (0, -1), (0, 2), (1, 0), (1, 2), (2, 1)];
+
 impl Universe {
+    /// Create a universe with every cell dead, clamping a zero width or
+    /// height to 1.
+    ///
+    /// The dimensions must already have passed `check_size`.
+    fn dead(width: u32, height: u32) -> Universe {
+        let width = width.max(1);
+        let height = height.max(1);
+
+        let size = (width * height) as usize;
+        let mut universe = Universe {
+            width,
+            height,
+            cells: FixedBitSet::with_capacity(size),
+            boundary: Boundary::default(),
+            rule: Rule::default(),
+            generation: 0,
+            history: VecDeque::new(),
+            history_limit: DEFAULT_HISTORY_LIMIT,
+            redo_stack: Vec::new(),
+            custom_patterns: HashMap::new(),
+            track_ages: false,
+            ages: Vec::new(),
+            colors: Vec::new(),
+            changed: Vec::new(),
+            last_births: 0,
+            last_deaths: 0,
+            scratch: FixedBitSet::new(),
+            neighbor_rows: Vec::new(),
+            neighbor_columns: Vec::new(),
+            initial: None,
+            pop_history: VecDeque::new(),
+            pop_history_limit: DEFAULT_POPULATION_HISTORY_LIMIT,
+            auto_grow_limit: (DEFAULT_AUTO_GROW_LIMIT, DEFAULT_AUTO_GROW_LIMIT),
+        };
+        universe.build_neighbor_table();
+        universe
+    }
+
+    /// Check that a universe of the given dimensions has few enough cells
+    /// for every cell index to fit in a `u32`.
+    fn check_size(width: u32, height: u32) -> Result<(), String> {
+        match width.checked_mul(height) {
+            Some(_) => Ok(()),
+            None => Err(format!(
+                "universe dimensions {}x{} have too many cells",
+                width, height
+            )),
+        }
+    }
+
     fn get_index(&self, row: u32, column: u32) -> usize {
         (row * self.width + column) as usize
     }
 
+    /// Get the coordinate `delta` away from `coord` along an axis of the
+    /// given size and edges, or `None` if it falls outside the universe.
+    fn neighbor_coord(coord: u32, delta: i32, size: u32, edge: Edge) -> Option<u32> {
+        let neighbor = coord as i64 + delta as i64;
+        match edge {
+            Edge::Wrap => Some(neighbor.rem_euclid(size as i64) as u32),
+            Edge::Dead => (0..size as i64)
+                .contains(&neighbor)
+                .then_some(neighbor as u32),
+            Edge::Reflect => Some(neighbor.clamp(0, size as i64 - 1) as u32),
+        }
+    }
+
+    /// Get the row `delta` away from `row`, or `None` if it falls outside
+    /// the universe.
+    fn neighbor_row(&self, row: u32, delta: i32) -> Option<u32> {
+        Universe::neighbor_coord(row, delta, self.height, self.boundary.edges().0)
+    }
+
+    /// Get the column `delta` away from `column`, or `None` if it falls
+    /// outside the universe.
+    fn neighbor_col(&self, column: u32, delta: i32) -> Option<u32> {
+        Universe::neighbor_coord(column, delta, self.width, self.boundary.edges().1)
+    }
+
+    /// Look up the rows above, on and below every row, and the columns left
+    /// of, on and right of every column, so that finding a cell's neighbors
+    /// doesn't have to wrap or bound-check each coordinate every tick.
+    ///
+    /// Must be called again whenever the dimensions or boundary change.
+    fn build_neighbor_table(&mut self) {
+        let (row_edge, col_edge) = self.boundary.edges();
+        let neighbors = |coord, size, edge| {
+            let mut neighbors =
+                [-1, 0, 1].map(|delta| Universe::neighbor_coord(coord, delta, size, edge));
+            // Wrapping around an axis shorter than 3 reaches the same
+            // coordinate more than once, so only keep the first.
+            if edge == Edge::Wrap {
+                if size < 3 {
+                    neighbors[2] = None;
+                }
+                if size < 2 {
+                    neighbors[0] = None;
+                }
+            }
+            neighbors
+        };
+        let neighbor_rows = (0..self.height)
+            .map(|row| {
+                neighbors(row, self.height, row_edge).map(|row| row.map(|row| row * self.width))
+            })
+            .collect();
+        let neighbor_columns = (0..self.width)
+            .map(|col| neighbors(col, self.width, col_edge))
+            .collect();
+
+        self.neighbor_rows = neighbor_rows;
+        self.neighbor_columns = neighbor_columns;
+    }
+
+    /// Get the indices of the cell at the given row and column and of its
+    /// neighbors, as a 3x3 grid with the cell itself in the middle.
+    ///
+    /// Neighbors that fall outside the universe are `None`.
+    fn neighborhood(&self, row: u32, column: u32) -> [[Option<usize>; 3]; 3] {
+        let columns = self.neighbor_columns[column as usize];
+        self.neighbor_rows[row as usize]
+            .map(|row_start| columns.map(|col| Some((row_start? + col?) as usize)))
+    }
+
     fn live_neighbor_count(&self, row: u32, column: u32) -> u8 {
         let mut count = 0;
-        for delta_row in [self.height - 1, 0, 1].iter().cloned() {
-            for delta_col in [self.width - 1, 0, 1].iter().cloned() {
-                if delta_row == 0 && delta_col == 0 {
+        for (delta_row, indices) in self.neighborhood(row, column).into_iter().enumerate() {
+            for (delta_col, idx) in indices.into_iter().enumerate() {
+                if (delta_row, delta_col) == (1, 1) {
                     continue;
                 }
-
-                let neighbor_row = (row + delta_row) % self.height;
-                let neighbor_col = (column + delta_col) % self.width;
-                let idx = self.get_index(neighbor_row, neighbor_col);
-                count += self.cells[idx] as u8;
+                if let Some(idx) = idx {
+                    count += self.cells[idx] as u8;
+                }
             }
         }
         count
     }
 
-    /// Get the dead and alive values of the entire universe.
-    pub fn get_cells(&self) -> &FixedBitSet {
-        &self.cells
+    /// Save the current cells so that the next change can be undone,
+    /// discarding the oldest saved state when the history is full.
+    ///
+    /// Anything previously undone can no longer be redone.
+    fn push_history(&mut self) {
+        self.redo_stack.clear();
+        self.save_history();
     }
 
-    /// Set cells to be alive in a universe by passing the row and column
-    /// of each cell as an array.
-    pub fn set_cells(&mut self, cells: &[(u32, u32)]) {
-        for (row, col) in cells.iter().cloned() {
-            let idx = self.get_index(row, col);
-            self.cells.set(idx, true);
+    fn save_history(&mut self) {
+        if self.history_limit == 0 {
+            return;
+        }
+        if self.history.len() >= self.history_limit {
+            self.history.pop_front();
         }
+        self.history
+            .push_back((self.cells.clone(), self.generation));
     }
-}
 
-#[wasm_bindgen]
-impl Universe {
-    pub fn tick(&mut self) {
-        let mut next = self.cells.clone();
+    /// Advance the universe by one generation without saving its history.
+    fn step(&mut self) {
+        if self.boundary == Boundary::AutoGrow {
+            self.grow_to_fit();
+        }
 
-        for row in 0..self.height {
-            for col in 0..self.width {
-                let idx = self.get_index(row, col);
-                let cell = self.cells[idx];
-                let live_neighbors = self.live_neighbor_count(row, col);
+        let dirty = self.dirty_cells();
+        let mut next = self.take_scratch();
+        match dirty {
+            Some(dirty) => {
+                next.clone_from(&self.cells);
+                for idx in dirty {
+                    let (row, col) = (idx as u32 / self.width, idx as u32 % self.width);
+                    next.set(idx, self.next_cell(row, col));
+                }
+            }
+            None => self.write_next_cells(&mut next),
+        }
+        self.advance(next);
+    }
 
-                next.set(
-                    idx,
-                    match (cell, live_neighbors) {
-                        (true, x) if x < 2 => false,
-                        (true, 2) | (true, 3) => true,
-                        (true, x) if x > 3 => false,
-                        (false, 3) => true,
-                        (otherwise, _) => otherwise,
-                    },
+    /// Grow the universe by `AUTO_GROW_MARGIN` rows or columns past each edge
+    /// that a live cell has reached, without growing past the auto-grow
+    /// limit.
+    ///
+    /// Growing past the top or left edge moves every cell down or right, in
+    /// the saved undo history too, so that it can still be undone.
+    fn grow_to_fit(&mut self) {
+        let Some((min_row, min_col, max_row, max_col)) = self.live_bounds() else {
+            return;
+        };
+
+        let (max_width, max_height) = self.auto_grow_limit;
+        let grow = |reached_start: bool, reached_end: bool, size: u32, max_size: u32| {
+            let room = max_size.saturating_sub(size);
+            let start = if reached_start {
+                AUTO_GROW_MARGIN.min(room)
+            } else {
+                0
+            };
+            let end = if reached_end {
+                AUTO_GROW_MARGIN.min(room - start)
+            } else {
+                0
+            };
+            (start, end)
+        };
+        let (top, bottom) = grow(
+            min_row == 0,
+            max_row == self.height - 1,
+            self.height,
+            max_height,
+        );
+        let (left, right) = grow(
+            min_col == 0,
+            max_col == self.width - 1,
+            self.width,
+            max_width,
+        );
+        if top + bottom + left + right == 0 {
+            return;
+        }
+
+        let (width, height) = (self.width + left + right, self.height + top + bottom);
+        let old_width = self.width as usize;
+        let grow_cells = |cells: &FixedBitSet| {
+            let mut grown = FixedBitSet::with_capacity((width * height) as usize);
+            for idx in cells.ones() {
+                let (row, col) = (
+                    idx / old_width + top as usize,
+                    idx % old_width + left as usize,
                 );
+                grown.insert(row * width as usize + col);
             }
+            grown
+        };
+
+        self.cells = grow_cells(&self.cells);
+        for (cells, _) in self.history.iter_mut() {
+            *cells = grow_cells(cells);
+        }
+        for (cells, _) in self.redo_stack.iter_mut() {
+            *cells = grow_cells(cells);
+        }
+        if let Some(initial) = &self.initial {
+            self.initial = Some(grow_cells(initial));
         }
 
-        self.cells = next;
+        self.width = width;
+        self.height = height;
+        self.forget_ages_and_colors();
+        self.build_neighbor_table();
+        self.forget_last_step();
     }
 
-    pub fn new(chance_of_life: Option<f64>) -> Universe {
-        utils::set_panic_hook();
+    /// Take the spare buffer to compute the next generation into, so that
+    /// it can be swapped with the current cells rather than allocating a new
+    /// buffer every generation.
+    fn take_scratch(&mut self) -> FixedBitSet {
+        let next = std::mem::take(&mut self.scratch);
+        match next.len() == self.cells.len() {
+            true => next,
+            false => FixedBitSet::with_capacity(self.cells.len()),
+        }
+    }
 
-        let width = 256;
-        let height = 256;
+    /// Replace the current cells with the next generation's.
+    fn advance(&mut self, next: FixedBitSet) {
+        if self.track_ages {
+            for (idx, age) in self.ages.iter_mut().enumerate() {
+                *age = match self.cells[idx] && next[idx] {
+                    true => age.saturating_add(1),
+                    false => 0,
+                };
+            }
+        }
 
-        let size = (width * height) as usize;
-        let mut cells = FixedBitSet::with_capacity(size);
+        self.changed.clear();
+        self.changed
+            .extend(self.cells.symmetric_difference(&next).map(|idx| idx as u32));
+        self.changed.sort_unstable();
+        self.last_births = self
+            .changed
+            .iter()
+            .filter(|&&idx| next[idx as usize])
+            .count() as u32;
+        self.last_deaths = self.changed.len() as u32 - self.last_births;
+        for &idx in &self.changed {
+            if let Some(color) = self.colors.get_mut(idx as usize) {
+                *color = 0;
+            }
+        }
+
+        self.scratch = std::mem::replace(&mut self.cells, next);
+        self.generation += 1;
+        self.record_population();
+    }
 
-        for idx in 0..width * height {
-            cells.set(
-                idx as usize,
-                match chance_of_life {
-                    Some(chance) => Math::random() < chance,
-                    None => Math::random() < 0.1,
-                },
-            );
+    fn record_population(&mut self) {
+        if self.pop_history_limit == 0 {
+            return;
+        }
+        if self.pop_history.len() >= self.pop_history_limit {
+            self.pop_history.pop_front();
         }
+        self.pop_history.push_back(self.population());
+    }
 
-        log! {
-            "Universe created with width: {}, height: {}, alive cells: {}",
-            width,
-            height,
-            cells.count_ones(0..cells.len())
-        };
+    /// Get the indices of the cells that could change in the next
+    /// generation: those that changed in the last one and their neighbors.
+    ///
+    /// Every other cell has the same neighborhood as a generation ago, so
+    /// it is bound to keep its state. Returns `None` when the whole universe
+    /// has to be recomputed instead, because the cells were edited since the
+    /// last generation or too many of them changed for this to pay off.
+    fn dirty_cells(&self) -> Option<Vec<usize>> {
+        let stepped = self.scratch.len() == self.cells.len()
+            && self.cells.symmetric_difference_count(&self.scratch) == self.changed.len()
+            && self
+                .changed
+                .iter()
+                .all(|&idx| self.cells[idx as usize] != self.scratch[idx as usize]);
+        if !stepped || self.changed.len() * 9 >= self.cells.len() {
+            return None;
+        }
 
-        Universe {
-            width,
-            height,
-            cells,
+        let mut dirty = Vec::with_capacity(self.changed.len() * 9);
+        for &idx in &self.changed {
+            let neighborhood = self.neighborhood(idx / self.width, idx % self.width);
+            dirty.extend(neighborhood.into_iter().flatten().flatten());
         }
+        dirty.sort_unstable();
+        dirty.dedup();
+        Some(dirty)
     }
 
-    pub fn width(&self) -> u32 {
-        self.width
+    /// Forget the last generation, so that the next one is computed from
+    /// scratch. Needed whenever the cells' neighborhoods or the rule change.
+    fn forget_last_step(&mut self) {
+        self.scratch = FixedBitSet::new();
     }
 
-    pub fn height(&self) -> u32 {
-        self.height
+    /// Reset the age of every cell to 0, if ages are being tracked.
+    fn reset_ages(&mut self) {
+        self.ages.clear();
+        if self.track_ages {
+            self.ages.resize(self.cells.len(), 0);
+        }
     }
 
-    pub fn cells(&self) -> *const u32 {
-        self.cells.as_slice().as_ptr() as *const u32
+    /// Forget the age and color of every cell, after an edit that may have
+    /// changed any of them.
+    fn forget_ages_and_colors(&mut self) {
+        self.reset_ages();
+        self.colors.clear();
     }
 
-    /// Set the width of the universe.
-    ///
-    /// Resets all cells to the dead state.
-    pub fn set_width(&mut self, width: u32) {
-        self.width = width;
-        let size = (self.width * self.height) as usize;
-        self.cells = FixedBitSet::with_capacity(size);
-        self.cells.clear();
+    /// Forget the age and color of the cell at the given index, after an
+    /// edit that may have changed its state.
+    fn forget_age_and_color(&mut self, idx: usize) {
+        if let Some(age) = self.ages.get_mut(idx) {
+            *age = 0;
+        }
+        if let Some(color) = self.colors.get_mut(idx) {
+            *color = 0;
+        }
     }
 
-    /// Set the height of the universe.
-    ///
-    /// Resets all cells to the dead state.
-    pub fn set_height(&mut self, height: u32) {
-        self.height = height;
-        let size = (self.width * self.height) as usize;
-        self.cells = FixedBitSet::with_capacity(size);
-        self.cells.clear();
+    /// Compute the cells of the next generation without advancing the
+    /// universe.
+    fn next_cells(&self) -> FixedBitSet {
+        let mut next = FixedBitSet::with_capacity(self.cells.len());
+        self.write_next_cells(&mut next);
+        next
     }
 
-    pub fn toggle_cell(&mut self, row: u32, column: u32) {
-        let idx = self.get_index(row, column);
-        self.cells.toggle(idx);
+    /// Write the cells of the next generation into `next`, which must be the
+    /// same length as the current cells.
+    fn write_next_cells(&self, next: &mut FixedBitSet) {
+        #[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
+        if self.cells.len() >= parallel::PARALLEL_MIN_CELLS {
+            return self.write_next_cells_parallel(next);
+        }
+
+        #[cfg(feature = "simd")]
+        return self.write_next_cells_simd(next);
+
+        #[cfg(not(feature = "simd"))]
+        self.write_next_cells_bitwise(next);
     }
 
-    pub fn clear(&mut self) {
-        self.cells.clear();
+    /// Whether the cell at the given row and column is alive in the next
+    /// generation.
+    fn next_cell(&self, row: u32, column: u32) -> bool {
+        let live_neighbors = self.live_neighbor_count(row, column);
+        match self.cells[self.get_index(row, column)] {
+            true => self.rule.survives(live_neighbors),
+            false => self.rule.is_born(live_neighbors),
+        }
     }
 
-    pub fn deploy(&mut self, pattern: &str, row: u32, column: u32) {
-        let pattern = match pattern.parse::<Pattern>() {
-            Ok(pattern) => match pattern {
-                Pattern::Glider => GLIDER_ALIVE_OFFSET.iter(),
-                Pattern::Pulsar => PULSAR_ALIVE_OFFSET.iter(),
-            },
-            Err(_) => return,
-        };
+    /// Get the color of the live cell at the given index for the immigration
+    /// variant, which is 1 unless it was set to 2.
+    fn cell_color_at(&self, idx: usize) -> u8 {
+        match self.colors.get(idx) {
+            Some(2) => 2,
+            _ => 1,
+        }
+    }
 
-        let alive_cells = pattern
-            .map(|(delta_row, delta_col)| {
-                let row = (row as i32 + delta_row) as u32;
-                let col = (column as i32 + delta_col) as u32;
-                (row % self.height, col % self.width)
-            })
-            .collect::<Vec<_>>();
+    /// Get the number of generations each cell has stayed alive for, if ages
+    /// are being tracked.
+    pub fn get_ages(&self) -> &[u16] {
+        &self.ages
+    }
+
+    /// Get the indices of the cells that changed state in the last tick.
+    pub fn get_changed_cells(&self) -> &[u32] {
+        &self.changed
+    }
+
+    /// Tick `steps` times, calling `callback` with the generation and
+    /// population after each tick.
+    pub fn run_with(&mut self, steps: u32, mut callback: impl FnMut(u64, u32)) {
+        for _ in 0..steps {
+            self.tick();
+            callback(self.generation, self.population());
+        }
+    }
+
+    /// Advance the universe by one generation like `tick`, but deciding
+    /// whether each cell is alive from whether it is currently alive and
+    /// its number of live neighbors with `rule`, instead of the universe's
+    /// own rule.
+    pub fn tick_with<F: Fn(bool, u8) -> bool>(&mut self, rule: F) {
+        self.push_history();
+        if self.boundary == Boundary::AutoGrow {
+            self.grow_to_fit();
+        }
+
+        let mut next = self.take_scratch();
+        for row in 0..self.height {
+            for col in 0..self.width {
+                let idx = self.get_index(row, col);
+                next.set(
+                    idx,
+                    rule(self.cells[idx], self.live_neighbor_count(row, col)),
+                );
+            }
+        }
+        self.advance(next);
+
+        // What changed in this generation was down to a different rule, so
+        // it says nothing about what will change in the next.
+        self.forget_last_step();
+    }
+
+    /// Compute the cells of the next generation in full with the scalar
+    /// bitwise kernel on the current thread, without advancing the universe.
+    ///
+    /// This is the kernel `tick` uses unless the `simd` feature is enabled,
+    /// which makes it useful for checking the other kernels against.
+    pub fn next_cells_scalar(&self) -> FixedBitSet {
+        let mut next = FixedBitSet::with_capacity(self.cells.len());
+        self.write_next_cells_bitwise(&mut next);
+        next
+    }
+
+    /// Get the dead and alive values of the entire universe.
+    pub fn get_cells(&self) -> &FixedBitSet {
+        &self.cells
+    }
 
-        self.set_cells(&alive_cells);
+    /// Set cells to be alive in a universe by passing the row and column
+    /// of each cell as an array.
+    ///
+    /// The resulting cells are what `reset` returns to.
+    pub fn set_cells(&mut self, cells: &[(u32, u32)]) {
+        self.insert_cells(cells);
+        self.mark_initial();
+    }
+
+    fn insert_cells(&mut self, cells: &[(u32, u32)]) {
+        for (row, col) in cells.iter().cloned() {
+            let idx = self.get_index(row, col);
+            if !self.cells.put(idx) {
+                self.forget_age_and_color(idx);
+            }
+        }
+    }
+
+    /// Get the colors of the cells as red, green, blue and alpha bytes in
+    /// row-major order, ready to upload as a WebGL texture.
+    pub fn to_rgba(&self, alive: [u8; 4], dead: [u8; 4]) -> Vec<u8> {
+        let mut rgba = Vec::with_capacity(self.cells.len() * 4);
+        for idx in 0..self.cells.len() {
+            rgba.extend_from_slice(match self.cells[idx] {
+                true => &alive,
+                false => &dead,
+            });
+        }
+        rgba
+    }
+
+    /// Get the dead and alive values of the entire universe as rows of
+    /// booleans.
+    pub fn to_bool_grid(&self) -> Vec<Vec<bool>> {
+        (0..self.height)
+            .map(|row| {
+                (0..self.width)
+                    .map(|col| self.cells[self.get_index(row, col)])
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Create a universe from rows of booleans, taking its width from the
+    /// first row and its height from the number of rows.
+    ///
+    /// Returns an error if any row has a different length from the first.
+    /// An empty grid gives a universe of a single dead cell.
+    pub fn from_bool_grid(grid: &[Vec<bool>]) -> Result<Universe, String> {
+        let width = grid.first().map_or(0, |row| row.len());
+        if let Some(row) = grid.iter().position(|cells| cells.len() != width) {
+            return Err(format!(
+                "row {} has {} cells, expected {}",
+                row,
+                grid[row].len(),
+                width
+            ));
+        }
+
+        let (width, height) = (
+            u32::try_from(width).map_err(|_| "grid is too wide".to_string())?,
+            u32::try_from(grid.len()).map_err(|_| "grid is too tall".to_string())?,
+        );
+        Universe::check_size(width, height)?;
+        let mut universe = Universe::dead(width, height);
+        for (row, cells) in grid.iter().enumerate() {
+            for (col, &alive) in cells.iter().enumerate() {
+                let idx = universe.get_index(row as u32, col as u32);
+                universe.cells.set(idx, alive);
+            }
+        }
+        universe.mark_initial();
+        Ok(universe)
+    }
+
+    /// Order the corners of an inclusive rectangle and clamp them to the
+    /// universe, returning `(min_row, min_col, max_row, max_col)`.
+    fn clamp_rect(&self, r0: u32, c0: u32, r1: u32, c1: u32) -> (u32, u32, u32, u32) {
+        (
+            r0.min(r1).min(self.height - 1),
+            c0.min(c1).min(self.width - 1),
+            r0.max(r1).min(self.height - 1),
+            c0.max(c1).min(self.width - 1),
+        )
+    }
+
+    /// Get the offsets of the live cells from the anchor of a custom or
+    /// built-in pattern, or `None` for an unknown pattern name.
+    fn pattern_offsets(&self, name: &str) -> Option<&[(i32, i32)]> {
+        match self.custom_patterns.get(&name.to_uppercase()) {
+            Some(offsets) => Some(offsets),
+            None => name
+                .parse::<Pattern>()
+                .ok()
+                .map(|pattern| pattern.offsets()),
+        }
+    }
+
+    /// Replace the cells with those of a universe with the given dimensions.
+    ///
+    /// The undo history is cleared, as its cells may no longer fit.
+    fn replace_cells(&mut self, width: u32, height: u32, cells: FixedBitSet) {
+        self.width = width;
+        self.height = height;
+        self.cells = cells;
+        self.initial = None;
+        self.history.clear();
+        self.redo_stack.clear();
+        self.forget_ages_and_colors();
+        self.build_neighbor_table();
+        self.forget_last_step();
+    }
+
+    /// Move every live cell to the row and column given by `to`, which must
+    /// be within the universe.
+    fn move_live_cells(&mut self, to: impl Fn(u32, u32) -> (u32, u32)) {
+        let mut moved = FixedBitSet::with_capacity(self.cells.len());
+        for (row, col) in self.live_cells() {
+            let (row, col) = to(row, col);
+            moved.insert(self.get_index(row, col));
+        }
+        self.cells = moved;
+        self.forget_ages_and_colors();
+    }
+
+    /// Run `f`, which may step the universe forward, then restore the
+    /// universe to how it was beforehand.
+    ///
+    /// The universe doesn't grow with the `AutoGrow` boundary meanwhile, as
+    /// the edges have the same neighbors as the `Dead` boundary.
+    fn look_ahead<T>(&mut self, f: impl FnOnce(&mut Universe) -> T) -> T {
+        let boundary = match self.boundary {
+            Boundary::AutoGrow => std::mem::replace(&mut self.boundary, Boundary::Dead),
+            boundary => boundary,
+        };
+        let cells = self.cells.clone();
+        let generation = self.generation;
+        let ages = self.ages.clone();
+        let colors = self.colors.clone();
+        let changed = self.changed.clone();
+        let (last_births, last_deaths) = (self.last_births, self.last_deaths);
+        let pop_history = self.pop_history.clone();
+
+        let result = f(self);
+
+        self.cells = cells;
+        self.generation = generation;
+        self.ages = ages;
+        self.colors = colors;
+        self.changed = changed;
+        (self.last_births, self.last_deaths) = (last_births, last_deaths);
+        self.pop_history = pop_history;
+        self.boundary = boundary;
+        result
+    }
+
+    /// Find the smallest number of generations, up to `max_period`, after
+    /// which the live cells are the same shape as now but moved elsewhere.
+    ///
+    /// Returns the period along with how many rows and columns the shape
+    /// moved by, or `None` if it never moved or changed shape. Edges that
+    /// wrap around can hide the movement, so this works best with dead
+    /// boundaries. The universe is restored to its current state afterwards.
+    pub fn detect_spaceship(&mut self, max_period: u32) -> Option<(u32, i32, i32)> {
+        let (min_row, min_col, _, _) = self.live_bounds()?;
+        let (_, _, offsets) = self.cropped_live_cells();
+
+        self.look_ahead(|universe| {
+            (1..=max_period).find_map(|period| {
+                universe.step();
+                let (row, col, _, _) = universe.live_bounds()?;
+                let delta_row = row as i32 - min_row as i32;
+                let delta_col = col as i32 - min_col as i32;
+                let moved = (delta_row, delta_col) != (0, 0);
+                (moved && universe.cropped_live_cells().2 == offsets)
+                    .then_some((period, delta_row, delta_col))
+            })
+        })
+    }
+
+    /// Iterate over the row and column of every live cell, in row-major
+    /// order.
+    pub fn live_cells(&self) -> impl Iterator<Item = (u32, u32)> + '_ {
+        self.cells
+            .ones()
+            .map(|idx| (idx as u32 / self.width, idx as u32 % self.width))
+    }
+
+    /// Get the smallest rectangle containing every live cell, as the
+    /// inclusive `(min_row, min_col, max_row, max_col)`, or `None` if every
+    /// cell is dead.
+    pub fn live_bounds(&self) -> Option<(u32, u32, u32, u32)> {
+        self.live_cells().fold(None, |bounds, (row, col)| {
+            Some(match bounds {
+                None => (row, col, row, col),
+                Some((min_row, min_col, max_row, max_col)) => (
+                    min_row.min(row),
+                    min_col.min(col),
+                    max_row.max(row),
+                    max_col.max(col),
+                ),
+            })
+        })
+    }
+
+    /// Get the smallest rectangle containing the live cells of a pattern, as
+    /// the inclusive `(min_row, min_col, max_row, max_col)` offsets from the
+    /// anchor it is deployed at.
+    ///
+    /// Returns `None` for an unknown pattern name or a pattern without any
+    /// live cells.
+    pub fn pattern_bounds(&self, name: &str) -> Option<(i32, i32, i32, i32)> {
+        self.pattern_offsets(name)?
+            .iter()
+            .fold(None, |bounds, &(row, col)| {
+                Some(match bounds {
+                    None => (row, col, row, col),
+                    Some((min_row, min_col, max_row, max_col)) => (
+                        min_row.min(row),
+                        min_col.min(col),
+                        max_row.max(row),
+                        max_col.max(col),
+                    ),
+                })
+            })
+    }
+
+    /// Get the size of the smallest rectangle containing every live cell,
+    /// along with the offsets of the live cells from its top left corner in
+    /// row-major order.
+    fn cropped_live_cells(&self) -> (u32, u32, Vec<(u32, u32)>) {
+        let Some((min_row, min_col, max_row, max_col)) = self.live_bounds() else {
+            return (0, 0, Vec::new());
+        };
+
+        let offsets = self
+            .live_cells()
+            .map(|(row, col)| (row - min_row, col - min_col))
+            .collect::<Vec<_>>();
+
+        (max_col - min_col + 1, max_row - min_row + 1, offsets)
+    }
+
+    /// Set cells to be alive at the given offsets from an anchor, wrapping
+    /// around the edges of the universe.
+    fn set_cells_at(&mut self, offsets: &[(u32, u32)], row: u32, column: u32) {
+        let alive_cells = offsets
+            .iter()
+            .map(|(delta_row, delta_col)| {
+                let row = (row as u64 + *delta_row as u64) % self.height as u64;
+                let col = (column as u64 + *delta_col as u64) % self.width as u64;
+                (row as u32, col as u32)
+            })
+            .collect::<Vec<_>>();
+
+        self.insert_cells(&alive_cells);
+    }
+
+    /// Switch to the rule from a pattern's header, falling back to Conway's
+    /// Game of Life if there was none or it could not be parsed.
+    fn set_pattern_rule(&mut self, rulestring: Option<&str>) {
+        self.rule = rulestring
+            .and_then(|rulestring| rulestring.parse().ok())
+            .unwrap_or_default();
+        self.forget_last_step();
+    }
+
+    /// Copy every cell in the top or left half of the universe onto its
+    /// mirror image across the given axis.
+    fn mirror(&mut self, axis: Axis) {
+        let mirror_rows = matches!(axis, Axis::Horizontal | Axis::Both);
+        let mirror_cols = matches!(axis, Axis::Vertical | Axis::Both);
+
+        for row in 0..self.height {
+            for col in 0..self.width {
+                let source_row = match mirror_rows {
+                    true => row.min(self.height - 1 - row),
+                    false => row,
+                };
+                let source_col = match mirror_cols {
+                    true => col.min(self.width - 1 - col),
+                    false => col,
+                };
+                let alive = self.cells[self.get_index(source_row, source_col)];
+                let idx = self.get_index(row, col);
+                self.cells.set(idx, alive);
+            }
+        }
+    }
+
+    /// Randomly set every cell to be alive with the given chance, drawing
+    /// values in `[0, 1)` from `rng`.
+    ///
+    /// Defaults to a chance of 0.1 when none is given.
+    fn fill_random(&mut self, chance_of_life: Option<f64>, mut rng: impl FnMut() -> f64) {
+        let chance = chance_of_life.unwrap_or(0.1);
+        for idx in 0..self.cells.len() {
+            self.cells.set(idx, rng() < chance);
+        }
+    }
+}
+
+impl fmt::Display for Universe {
+    /// Draw the universe with a `◼` for each live cell and a `◻` for each
+    /// dead one, ending every row with a newline.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.render('◼', '◻'))
+    }
+}
+
+impl fmt::Debug for Universe {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Universe")
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .field("generation", &self.generation)
+            .field("rule", &format_args!("{}", self.rule))
+            .field("live", &self.cells.ones().collect::<Vec<_>>())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Universes are equal when they have the same dimensions and live cells,
+/// whatever their generation, rule or history.
+impl PartialEq for Universe {
+    fn eq(&self, other: &Universe) -> bool {
+        self.width == other.width && self.height == other.height && self.cells == other.cells
+    }
+}
+
+impl Eq for Universe {}
+
+/// The default universe is the same size as `Universe::new`'s, with every
+/// cell dead.
+impl Default for Universe {
+    fn default() -> Universe {
+        Universe::dead(256, 256)
+    }
+}
+
+/// Get the canonical name of every pattern that can be deployed.
+#[wasm_bindgen]
+pub fn available_patterns() -> Vec<String> {
+    PATTERNS
+        .iter()
+        .map(|(names, _)| names[0].to_string())
+        .collect()
+}
+
+/// Get the row and column of every cell that differs between two universes,
+/// in row-major order.
+///
+/// Returns an error if the universes have different dimensions.
+pub fn diff(a: &Universe, b: &Universe) -> Result<Vec<(u32, u32)>, String> {
+    if (a.width, a.height) != (b.width, b.height) {
+        return Err(format!(
+            "cannot compare a {}x{} universe with a {}x{} one",
+            a.width, a.height, b.width, b.height
+        ));
+    }
+
+    let mut differences = a.cells.symmetric_difference(&b.cells).collect::<Vec<_>>();
+    differences.sort_unstable();
+    Ok(differences
+        .into_iter()
+        .map(|idx| (idx as u32 / a.width, idx as u32 % a.width))
+        .collect())
+}
+
+#[wasm_bindgen]
+impl Universe {
+    pub fn tick(&mut self) {
+        self.push_history();
+        self.step();
+    }
+
+    /// Advance the universe by one generation of the immigration variant of
+    /// Conway's Game of Life, in which every live cell has one of two colors.
+    ///
+    /// Cells live and die by the B3/S23 rule whatever the universe's rule
+    /// is. Surviving cells keep their color, and cells that are born take the
+    /// color of the majority of their three live neighbors.
+    pub fn tick_immigration(&mut self) {
+        self.push_history();
+        if self.boundary == Boundary::AutoGrow {
+            self.grow_to_fit();
+        }
+        self.colors.resize(self.cells.len(), 0);
+
+        let rule = Rule::default();
+        let mut next = self.take_scratch();
+        let mut colors = vec![0; self.cells.len()];
+        for row in 0..self.height {
+            for col in 0..self.width {
+                let mut color_counts = [0u8; 2];
+                for idx in self.neighborhood(row, col).into_iter().flatten().flatten() {
+                    if self.cells[idx] {
+                        color_counts[self.cell_color_at(idx) as usize - 1] += 1;
+                    }
+                }
+
+                let idx = self.get_index(row, col);
+                let live_neighbors = color_counts[0] + color_counts[1] - self.cells[idx] as u8;
+                let (alive, color) = match self.cells[idx] {
+                    true => (rule.survives(live_neighbors), self.cell_color_at(idx)),
+                    false => (
+                        rule.is_born(live_neighbors),
+                        if color_counts[1] > color_counts[0] {
+                            2
+                        } else {
+                            1
+                        },
+                    ),
+                };
+                next.set(idx, alive);
+                colors[idx] = if alive { color } else { 0 };
+            }
+        }
+
+        self.advance(next);
+        self.colors = colors;
+
+        // What changed in this generation was down to B3/S23 rather than the
+        // universe's own rule, so it says nothing about what will change in
+        // the next.
+        self.forget_last_step();
+    }
+
+    /// Advance the universe by `steps` generations in one call, avoiding a
+    /// call across the JS boundary for every generation.
+    ///
+    /// All of the generations are undone together by a single undo.
+    pub fn tick_many(&mut self, steps: u32) {
+        if steps == 0 {
+            return;
+        }
+
+        self.push_history();
+        for _ in 0..steps {
+            self.step();
+        }
+    }
+
+    /// Advance the universe by `steps` generations like `tick_many`, getting
+    /// the population after each of them.
+    pub fn tick_collect(&mut self, steps: u32) -> Vec<u32> {
+        if steps == 0 {
+            return Vec::new();
+        }
+
+        self.push_history();
+        (0..steps)
+            .map(|_| {
+                self.step();
+                self.population()
+            })
+            .collect()
+    }
+
+    /// Tick until the universe returns to how it was `period` generations
+    /// earlier, or for at most `max_steps` generations, returning the number
+    /// of generations advanced.
+    ///
+    /// With the default period of 1, this stops once nothing changes;
+    /// a longer period also stops for oscillators whose period divides it.
+    /// All of the generations are undone together by a single undo.
+    pub fn tick_until_stable(&mut self, max_steps: u32, period: Option<u32>) -> u32 {
+        let period = period.unwrap_or(1).max(1) as usize;
+        if max_steps == 0 {
+            return 0;
+        }
+
+        self.push_history();
+        let mut recent = VecDeque::with_capacity(period);
+        for steps in 1..=max_steps {
+            if recent.len() == period {
+                recent.pop_front();
+            }
+            recent.push_back(self.cells.clone());
+            self.step();
+
+            if recent.len() == period && recent.front() == Some(&self.cells) {
+                return steps;
+            }
+        }
+        max_steps
+    }
+
+    /// Tick until the universe revisits one of its last `window` states, or
+    /// for at most `max_steps` generations, returning the length of the
+    /// cycle found, or 0 if there was none.
+    ///
+    /// States are compared by `state_hash`, so this catches still lifes
+    /// (with a cycle of length 1) and oscillators with periods up to the
+    /// window, which defaults to 64. All of the generations are undone
+    /// together by a single undo.
+    pub fn run_until_cycle(&mut self, max_steps: u32, window: Option<u32>) -> u32 {
+        let window = window.unwrap_or(DEFAULT_CYCLE_WINDOW).max(1) as usize;
+        if max_steps == 0 {
+            return 0;
+        }
+
+        self.push_history();
+        let mut recent = VecDeque::with_capacity(window);
+        for _ in 0..max_steps {
+            if recent.len() == window {
+                recent.pop_front();
+            }
+            recent.push_back(self.state_hash());
+            self.step();
+
+            let hash = self.state_hash();
+            if let Some(age) = recent.iter().rev().position(|&recent| recent == hash) {
+                return age as u32 + 1;
+            }
+        }
+        0
+    }
+
+    /// Tick `steps` times, calling the JS `callback` with the generation
+    /// and population after each tick.
+    ///
+    /// Stops early with the exception if the callback throws.
+    pub fn run(&mut self, steps: u32, callback: &js_sys::Function) -> Result<(), JsValue> {
+        for _ in 0..steps {
+            self.tick();
+            callback.call2(
+                &JsValue::NULL,
+                &JsValue::from(self.generation),
+                &JsValue::from(self.population()),
+            )?;
+        }
+        Ok(())
+    }
+
+    pub fn new(chance_of_life: Option<f64>) -> Universe {
+        Universe::random(256, 256, chance_of_life)
+    }
+
+    /// Create a randomly filled universe with the given dimensions.
+    ///
+    /// A zero width or height is clamped to 1. Fails if the universe would
+    /// have more than `u32::MAX` cells.
+    pub fn with_size(
+        width: u32,
+        height: u32,
+        chance_of_life: Option<f64>,
+    ) -> Result<Universe, String> {
+        Universe::check_size(width, height)?;
+        Ok(Universe::random(width, height, chance_of_life))
+    }
+
+    fn random(width: u32, height: u32, chance_of_life: Option<f64>) -> Universe {
+        utils::set_panic_hook();
+
+        let mut universe = Universe::dead(width, height);
+        universe.fill_random(chance_of_life, Math::random);
+
+        log! {
+            "Universe created with width: {}, height: {}, alive cells: {}",
+            universe.width,
+            universe.height,
+            universe.cells.count_ones(..)
+        };
+
+        universe.mark_initial();
+        universe
+    }
+
+    /// Create a universe whose cells are filled from a deterministic PRNG.
+    ///
+    /// Two universes created with the same seed and chance of life have
+    /// identical cells.
+    pub fn new_seeded(seed: u64, chance_of_life: Option<f64>) -> Universe {
+        utils::set_panic_hook();
+
+        let mut universe = Universe::dead(256, 256);
+        let mut rng = utils::XorShift64::new(seed);
+        universe.fill_random(chance_of_life, || rng.next_f64());
+
+        universe.mark_initial();
+        universe
+    }
+
+    /// Create a universe from lines of text, with a row for each line and a
+    /// live cell for each `alive` character.
+    ///
+    /// The universe is as wide as the longest line, and any other character
+    /// is a dead cell.
+    pub fn from_ascii(art: &str, alive: char) -> Universe {
+        let width = art.lines().map(|line| line.chars().count()).max();
+        let height = art.lines().count();
+
+        let mut universe = Universe::dead(width.unwrap_or(0) as u32, height as u32);
+        for (row, line) in art.lines().enumerate() {
+            for (col, symbol) in line.chars().enumerate() {
+                if symbol == alive {
+                    let idx = universe.get_index(row as u32, col as u32);
+                    universe.cells.insert(idx);
+                }
+            }
+        }
+        universe.mark_initial();
+        universe
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn cells(&self) -> *const u32 {
+        self.cells.as_slice().as_ptr() as *const u32
+    }
+
+    /// Get a copy of the bits of the cells, as read through `cells`, which
+    /// front-ends can use without reading the wasm memory directly.
+    ///
+    /// This is slower than `cells` as it copies the whole buffer every call.
+    /// The bits are padded to a whole number of the bitset's blocks.
+    pub fn cells_bytes(&self) -> Vec<u8> {
+        self.cells
+            .as_slice()
+            .iter()
+            .flat_map(|block| block.to_le_bytes())
+            .collect()
+    }
+
+    /// Get the indices of the cells that changed state in the last tick, with
+    /// `changed_count` entries.
+    pub fn changed_cells(&self) -> *const u32 {
+        self.changed.as_ptr()
+    }
+
+    pub fn changed_count(&self) -> u32 {
+        self.changed.len() as u32
+    }
+
+    /// Get the number of dead cells that came alive in the last tick.
+    pub fn last_births(&self) -> u32 {
+        self.last_births
+    }
+
+    /// Get the number of live cells that died in the last tick.
+    pub fn last_deaths(&self) -> u32 {
+        self.last_deaths
+    }
+
+    /// Get the number of generations each cell has stayed alive for, with
+    /// one `u16` per cell.
+    ///
+    /// Only meaningful while ages are being tracked.
+    pub fn cell_ages(&self) -> *const u16 {
+        self.ages.as_ptr()
+    }
+
+    /// Set whether the number of generations each cell has stayed alive for
+    /// is tracked, at the cost of extra work every tick.
+    ///
+    /// Every age starts at 0 when tracking is enabled.
+    pub fn set_track_ages(&mut self, track_ages: bool) {
+        self.track_ages = track_ages;
+        self.reset_ages();
+    }
+
+    /// Set the width of the universe.
+    ///
+    /// Resets all cells to the dead state. A zero width is clamped to 1, and
+    /// a width too large for the current height is clamped to fit.
+    pub fn set_width(&mut self, width: u32) {
+        let width = width.clamp(1, u32::MAX / self.height);
+        let cells = FixedBitSet::with_capacity((width * self.height) as usize);
+        self.replace_cells(width, self.height, cells);
+        self.generation = 0;
+    }
+
+    /// Set the height of the universe.
+    ///
+    /// Resets all cells to the dead state. A zero height is clamped to 1, and
+    /// a height too large for the current width is clamped to fit.
+    pub fn set_height(&mut self, height: u32) {
+        let height = height.clamp(1, u32::MAX / self.width);
+        let cells = FixedBitSet::with_capacity((self.width * height) as usize);
+        self.replace_cells(self.width, height, cells);
+        self.generation = 0;
+    }
+
+    /// Get the number of cells the universe holds, which is always exactly
+    /// its width times its height.
+    pub fn capacity(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// Get the number of live cells.
+    pub fn population(&self) -> u32 {
+        self.cells.count_ones(0..self.cells.len()) as u32
+    }
+
+    /// Get the population after each of the most recent generations, oldest
+    /// first, so that it can be plotted without sampling every frame.
+    pub fn population_history(&self) -> Vec<u32> {
+        self.pop_history.iter().copied().collect()
+    }
+
+    /// Set how many generations' populations are kept, discarding the
+    /// oldest ones beyond the new limit.
+    pub fn set_population_history_limit(&mut self, limit: usize) {
+        self.pop_history_limit = limit;
+        let excess = self.pop_history.len().saturating_sub(limit);
+        self.pop_history.drain(..excess);
+    }
+
+    /// Get the fraction of cells that are alive, from 0 to 1.
+    pub fn density(&self) -> f64 {
+        match self.cells.len() {
+            0 => 0.0,
+            len => self.population() as f64 / len as f64,
+        }
+    }
+
+    /// Get the number of live cells within the inclusive rectangle between
+    /// two corners, clamped to the universe.
+    pub fn population_in_rect(&self, r0: u32, c0: u32, r1: u32, c1: u32) -> u32 {
+        let (min_row, min_col, max_row, max_col) = self.clamp_rect(r0, c0, r1, c1);
+
+        let mut count = 0;
+        for row in min_row..=max_row {
+            for col in min_col..=max_col {
+                count += self.cells[self.get_index(row, col)] as u32;
+            }
+        }
+        count
+    }
+
+    /// Whether every cell is dead.
+    pub fn is_extinct(&self) -> bool {
+        self.population() == 0
+    }
+
+    /// Whether the next generation would be identical to the current one.
+    pub fn is_stable(&self) -> bool {
+        self.next_cells() == self.cells
+    }
+
+    /// Get a hash of the dimensions and live cells of the universe, which
+    /// stays the same across builds and platforms.
+    pub fn state_hash(&self) -> u64 {
+        let mut hasher = utils::Fnv64::new();
+        hasher.write(&self.width.to_le_bytes());
+        hasher.write(&self.height.to_le_bytes());
+        for idx in self.cells.ones() {
+            hasher.write(&(idx as u32).to_le_bytes());
+        }
+        hasher.finish()
+    }
+
+    /// Find the smallest number of generations, up to `max_period`, after
+    /// which the universe returns to its current state.
+    ///
+    /// The universe is restored to its current state afterwards.
+    pub fn detect_period(&mut self, max_period: u32) -> Option<u32> {
+        let cells = self.cells.clone();
+        self.look_ahead(|universe| {
+            (1..=max_period).find(|_| {
+                universe.step();
+                universe.cells == cells
+            })
+        })
+    }
+
+    /// Change the dimensions of the universe, keeping each live cell at the
+    /// same row and column if it still fits.
+    ///
+    /// A zero width or height is clamped to 1. The undo history is cleared.
+    /// Fails without changing the universe if it would have more than
+    /// `u32::MAX` cells.
+    pub fn resize(&mut self, width: u32, height: u32) -> Result<(), String> {
+        Universe::check_size(width, height)?;
+        let mut resized = Universe::dead(width, height);
+        for (row, col) in self.live_cells() {
+            if row < resized.height && col < resized.width {
+                let resized_idx = resized.get_index(row, col);
+                resized.cells.insert(resized_idx);
+            }
+        }
+
+        self.replace_cells(resized.width, resized.height, resized.cells);
+        Ok(())
+    }
+
+    /// Rotate the whole universe a quarter turn clockwise, swapping its
+    /// width and height.
+    ///
+    /// The undo history is cleared.
+    pub fn rotate_cw(&mut self) {
+        let mut rotated = FixedBitSet::with_capacity(self.cells.len());
+        for (row, col) in self.live_cells() {
+            rotated.insert((col * self.height + (self.height - 1 - row)) as usize);
+        }
+        self.replace_cells(self.height, self.width, rotated);
+    }
+
+    /// Reflect the whole universe across its main diagonal, moving each cell
+    /// from row `r` and column `c` to row `c` and column `r`, and swapping its
+    /// width and height.
+    ///
+    /// The undo history is cleared.
+    pub fn transpose(&mut self) {
+        let mut transposed = FixedBitSet::with_capacity(self.cells.len());
+        for (row, col) in self.live_cells() {
+            transposed.insert((col * self.height + row) as usize);
+        }
+        self.replace_cells(self.height, self.width, transposed);
+    }
+
+    /// Get the number of generations that have elapsed.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn reset_generation(&mut self) {
+        self.generation = 0;
+    }
+
+    pub fn boundary(&self) -> Boundary {
+        self.boundary
+    }
+
+    /// Set how neighbors are found for cells on the edges of the universe.
+    pub fn set_boundary(&mut self, boundary: Boundary) {
+        self.boundary = boundary;
+        self.build_neighbor_table();
+        self.forget_last_step();
+    }
+
+    /// Set the largest width and height the universe grows to with the
+    /// `AutoGrow` boundary, which default to 1024.
+    ///
+    /// The universe is never shrunk to fit a smaller limit, and the height
+    /// is clamped so that the universe has at most `u32::MAX` cells.
+    pub fn set_auto_grow_limit(&mut self, max_width: u32, max_height: u32) {
+        let max_width = max_width.max(1);
+        self.auto_grow_limit = (max_width, max_height.min(u32::MAX / max_width));
+    }
+
+    /// Set the birth and survival rule from a rulestring in B/S notation,
+    /// such as `B3/S23` for Conway's Game of Life.
+    ///
+    /// Returns false, leaving the rule unchanged, if the rulestring could
+    /// not be parsed.
+    pub fn set_rule(&mut self, rulestring: &str) -> bool {
+        match rulestring.parse::<Rule>() {
+            Ok(rule) => {
+                self.rule = rule;
+                self.forget_last_step();
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Set the birth and survival rule to a named ruleset: Conway,
+    /// HighLife, DayAndNight or Seeds.
+    ///
+    /// Returns false, leaving the rule unchanged, for an unknown name.
+    pub fn set_ruleset(&mut self, name: &str) -> bool {
+        match name.parse::<Ruleset>() {
+            Ok(ruleset) => self.set_rule(ruleset.rulestring()),
+            Err(_) => false,
+        }
+    }
+
+    /// Whether the cell at the given row and column is alive.
+    ///
+    /// Returns false for coordinates outside the universe.
+    pub fn get_cell(&self, row: u32, column: u32) -> bool {
+        row < self.height && column < self.width && self.cells[self.get_index(row, column)]
+    }
+
+    /// Get the color of the cell at the given row and column for the
+    /// immigration variant: 0 for a dead cell, and 1 or 2 for a live one.
+    ///
+    /// Live cells are color 1 unless set otherwise. Returns 0 for coordinates
+    /// outside the universe.
+    pub fn cell_color(&self, row: u32, column: u32) -> u8 {
+        match self.get_cell(row, column) {
+            true => self.cell_color_at(self.get_index(row, column)),
+            false => 0,
+        }
+    }
+
+    /// Set the cell at the given row and column to be alive with color 1 or
+    /// 2 for the immigration variant, or dead for color 0.
+    ///
+    /// Any other color is treated as 2. Coordinates outside the universe are
+    /// ignored.
+    pub fn set_cell_color(&mut self, row: u32, column: u32, color: u8) {
+        if row >= self.height || column >= self.width {
+            return;
+        }
+
+        self.set_cell(row, column, color != 0);
+        let idx = self.get_index(row, column);
+        self.colors.resize(self.cells.len(), 0);
+        self.colors[idx] = color.min(2);
+    }
+
+    /// Get the number of live neighbors of the cell at the given row and
+    /// column, found according to the boundary.
+    ///
+    /// Returns 0 for coordinates outside the universe.
+    pub fn neighbor_count(&self, row: u32, column: u32) -> u8 {
+        match row < self.height && column < self.width {
+            true => self.live_neighbor_count(row, column),
+            false => 0,
+        }
+    }
+
+    /// Get the number of live cells other than the cell at the given row and
+    /// column that are at most `radius` rows and columns away from it,
+    /// wrapping around the edges of the universe whatever its boundary.
+    ///
+    /// Each cell is counted once, even if the radius is large enough to
+    /// wrap around the universe onto it again. Returns 0 for coordinates
+    /// outside the universe.
+    pub fn neighbor_count_radius(&self, row: u32, column: u32, radius: u32) -> u32 {
+        if row >= self.height || column >= self.width {
+            return 0;
+        }
+
+        let coords = |coord: u32, size: u32| {
+            let radius = radius.min(size) as i32;
+            let mut coords = (-radius..=radius)
+                .filter_map(|delta| Universe::neighbor_coord(coord, delta, size, Edge::Wrap))
+                .collect::<Vec<_>>();
+            coords.sort_unstable();
+            coords.dedup();
+            coords
+        };
+
+        let columns = coords(column, self.width);
+        let mut count = 0;
+        for neighbor_row in coords(row, self.height) {
+            for &neighbor_col in &columns {
+                count += self.cells[self.get_index(neighbor_row, neighbor_col)] as u32;
+            }
+        }
+        count - self.cells[self.get_index(row, column)] as u32
+    }
+
+    /// Set the cell at the given row and column to be alive or dead.
+    ///
+    /// Coordinates outside the universe are ignored.
+    pub fn set_cell(&mut self, row: u32, column: u32, alive: bool) {
+        if row >= self.height || column >= self.width || self.get_cell(row, column) == alive {
+            return;
+        }
+
+        self.push_history();
+        let idx = self.get_index(row, column);
+        self.cells.set(idx, alive);
+        self.forget_age_and_color(idx);
+    }
+
+    /// Set every cell in the inclusive rectangle between two corners to be
+    /// alive or dead, clamping the corners to the universe.
+    pub fn fill_rect(&mut self, r0: u32, c0: u32, r1: u32, c1: u32, alive: bool) {
+        self.push_history();
+
+        let (min_row, min_col, max_row, max_col) = self.clamp_rect(r0, c0, r1, c1);
+        for row in min_row..=max_row {
+            for col in min_col..=max_col {
+                let idx = self.get_index(row, col);
+                if self.cells[idx] != alive {
+                    self.cells.set(idx, alive);
+                    self.forget_age_and_color(idx);
+                }
+            }
+        }
+    }
+
+    /// Set every cell on the straight line between two endpoints, inclusive,
+    /// to be alive or dead, using Bresenham's line algorithm.
+    ///
+    /// Cells of the line outside the universe are ignored.
+    pub fn draw_line(&mut self, r0: u32, c0: u32, r1: u32, c1: u32, alive: bool) {
+        self.push_history();
+
+        let (mut row, mut col) = (r0 as i64, c0 as i64);
+        let (end_row, end_col) = (r1 as i64, c1 as i64);
+        let delta_row = -(end_row - row).abs();
+        let delta_col = (end_col - col).abs();
+        let step_row = if row < end_row { 1 } else { -1 };
+        let step_col = if col < end_col { 1 } else { -1 };
+        let mut error = delta_col + delta_row;
+
+        loop {
+            if row < self.height as i64 && col < self.width as i64 {
+                let idx = self.get_index(row as u32, col as u32);
+                if self.cells[idx] != alive {
+                    self.cells.set(idx, alive);
+                    self.forget_age_and_color(idx);
+                }
+            }
+            if (row, col) == (end_row, end_col) {
+                break;
+            }
+
+            let doubled_error = 2 * error;
+            if doubled_error >= delta_row {
+                error += delta_row;
+                col += step_col;
+            }
+            if doubled_error <= delta_col {
+                error += delta_col;
+                row += step_row;
+            }
+        }
+    }
+
+    /// Set the cell at the given row and column, and every cell connected
+    /// to it horizontally or vertically through cells of the same state, to
+    /// be alive or dead.
+    ///
+    /// The fill stops at the edges of the universe rather than wrapping
+    /// around. Coordinates outside the universe are ignored.
+    pub fn flood_fill(&mut self, row: u32, column: u32, alive: bool) {
+        if row >= self.height || column >= self.width || self.get_cell(row, column) == alive {
+            return;
+        }
+
+        self.push_history();
+        let mut stack = vec![(row, column)];
+        while let Some((row, col)) = stack.pop() {
+            let idx = self.get_index(row, col);
+            if self.cells[idx] == alive {
+                continue;
+            }
+            self.cells.set(idx, alive);
+            self.forget_age_and_color(idx);
+
+            let neighbors = [
+                (row.checked_sub(1), Some(col)),
+                (
+                    row.checked_add(1).filter(|&row| row < self.height),
+                    Some(col),
+                ),
+                (Some(row), col.checked_sub(1)),
+                (
+                    Some(row),
+                    col.checked_add(1).filter(|&col| col < self.width),
+                ),
+            ];
+            for (row, col) in neighbors {
+                if let (Some(row), Some(col)) = (row, col) {
+                    if self.cells[self.get_index(row, col)] != alive {
+                        stack.push((row, col));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Toggle every cell in the inclusive rectangle between two corners,
+    /// clamping the corners to the universe.
+    pub fn toggle_rect(&mut self, r0: u32, c0: u32, r1: u32, c1: u32) {
+        self.push_history();
+
+        let (min_row, min_col, max_row, max_col) = self.clamp_rect(r0, c0, r1, c1);
+        for row in min_row..=max_row {
+            for col in min_col..=max_col {
+                let idx = self.get_index(row, col);
+                self.cells.toggle(idx);
+                self.forget_age_and_color(idx);
+            }
+        }
+    }
+
+    /// Copy the cells in the inclusive rectangle between two corners,
+    /// clamped to the universe, so that its top left corner is at the given
+    /// row and column, wrapping around the edges of the universe.
+    ///
+    /// Both live and dead cells are copied, and the source and destination
+    /// may overlap.
+    pub fn copy_region(&mut self, r0: u32, c0: u32, r1: u32, c1: u32, row: u32, column: u32) {
+        self.push_history();
+
+        let (min_row, min_col, max_row, max_col) = self.clamp_rect(r0, c0, r1, c1);
+        let region = (min_row..=max_row)
+            .flat_map(|src_row| (min_col..=max_col).map(move |src_col| (src_row, src_col)))
+            .map(|(src_row, src_col)| {
+                let alive = self.cells[self.get_index(src_row, src_col)];
+                (src_row - min_row, src_col - min_col, alive)
+            })
+            .collect::<Vec<_>>();
+
+        for (delta_row, delta_col, alive) in region {
+            let dst_row = (row as u64 + delta_row as u64) % self.height as u64;
+            let dst_col = (column as u64 + delta_col as u64) % self.width as u64;
+            let idx = self.get_index(dst_row as u32, dst_col as u32);
+            self.cells.set(idx, alive);
+            self.forget_age_and_color(idx);
+        }
+    }
+
+    pub fn toggle_cell(&mut self, row: u32, column: u32) {
+        self.push_history();
+        let idx = self.get_index(row, column);
+        self.cells.toggle(idx);
+        self.forget_age_and_color(idx);
+    }
+
+    /// Randomly set every cell to be alive with the given chance, reusing
+    /// the existing cells rather than creating a new universe.
+    ///
+    /// Resets the generation counter, like `clear`.
+    pub fn randomize(&mut self, chance_of_life: Option<f64>) {
+        self.push_history();
+        self.fill_random(chance_of_life, Math::random);
+        self.generation = 0;
+        self.forget_ages_and_colors();
+        self.mark_initial();
+    }
+
+    /// Randomly set the cells like `randomize`, then mirror them across the
+    /// given axis so that the universe is exactly symmetric.
+    pub fn randomize_symmetric(&mut self, chance_of_life: Option<f64>, axis: Axis) {
+        self.push_history();
+        self.fill_random(chance_of_life, Math::random);
+        self.mirror(axis);
+        self.generation = 0;
+        self.forget_ages_and_colors();
+        self.mark_initial();
+    }
+
+    /// Remember the current cells as the initial state, which `reset`
+    /// returns to.
+    ///
+    /// This happens automatically when a universe is created, randomized or
+    /// has its cells set with `set_cells`.
+    pub fn mark_initial(&mut self) {
+        self.initial = Some(self.cells.clone());
+    }
+
+    /// Save the cells and generation so that they can be restored later,
+    /// more cheaply than cloning the whole universe.
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.clone(),
+            generation: self.generation,
+        }
+    }
+
+    /// Restore the cells and generation from a snapshot.
+    ///
+    /// The undo history is cleared if the universe has been resized since the
+    /// snapshot was taken.
+    pub fn restore(&mut self, snapshot: &Snapshot) {
+        match (self.width, self.height) == (snapshot.width, snapshot.height) {
+            true => {
+                self.push_history();
+                self.cells.clone_from(&snapshot.cells);
+                self.forget_ages_and_colors();
+            }
+            false => self.replace_cells(snapshot.width, snapshot.height, snapshot.cells.clone()),
+        }
+        self.generation = snapshot.generation;
+    }
+
+    /// Restore the initial state and reset the generation counter.
+    ///
+    /// Returns false if there is no initial state, as the dimensions have
+    /// changed since it was marked.
+    pub fn reset(&mut self) -> bool {
+        let Some(initial) = self.initial.clone() else {
+            return false;
+        };
+
+        self.push_history();
+        self.cells = initial;
+        self.generation = 0;
+        self.forget_ages_and_colors();
+        self.pop_history.clear();
+        true
+    }
+
+    pub fn clear(&mut self) {
+        self.push_history();
+        self.cells.clear();
+        self.generation = 0;
+        self.forget_ages_and_colors();
+        self.pop_history.clear();
+    }
+
+    /// Flip every cell, so that live cells die and dead cells come alive.
+    pub fn invert(&mut self) {
+        self.push_history();
+        self.cells.toggle_range(..);
+        self.forget_ages_and_colors();
+    }
+
+    /// Move every live cell so that the smallest rectangle containing them
+    /// is centered in the universe.
+    ///
+    /// Does nothing if every cell is dead.
+    pub fn center(&mut self) {
+        let (width, height, offsets) = self.cropped_live_cells();
+        if offsets.is_empty() {
+            return;
+        }
+
+        self.push_history();
+        self.cells.clear();
+        self.set_cells_at(
+            &offsets,
+            (self.height - height) / 2,
+            (self.width - width) / 2,
+        );
+        self.forget_ages_and_colors();
+    }
+
+    /// Move every live cell by the given number of rows and columns.
+    ///
+    /// Cells moved past an edge that wraps around reappear on the other
+    /// side, are clamped to a reflective edge, and are lost past a dead one.
+    pub fn shift(&mut self, delta_row: i32, delta_col: i32) {
+        self.push_history();
+
+        let mut shifted = FixedBitSet::with_capacity(self.cells.len());
+        for (row, col) in self.live_cells() {
+            if let (Some(row), Some(col)) = (
+                self.neighbor_row(row, delta_row),
+                self.neighbor_col(col, delta_col),
+            ) {
+                shifted.insert(self.get_index(row, col));
+            }
+        }
+        self.cells = shifted;
+        self.forget_ages_and_colors();
+    }
+
+    /// Mirror the whole universe left to right.
+    pub fn flip_horizontal(&mut self) {
+        self.push_history();
+        let width = self.width;
+        self.move_live_cells(|row, col| (row, width - 1 - col));
+    }
+
+    /// Mirror the whole universe top to bottom.
+    pub fn flip_vertical(&mut self) {
+        self.push_history();
+        let height = self.height;
+        self.move_live_cells(|row, col| (height - 1 - row, col));
+    }
+
+    /// Deploy a named pattern around the given row and column.
+    ///
+    /// Returns false, leaving the universe unchanged, for an unknown
+    /// pattern name.
+    pub fn deploy(&mut self, pattern: &str, row: u32, column: u32) -> bool {
+        self.deploy_oriented(pattern, row, column, 0)
+    }
+
+    /// Deploy a pattern rotated clockwise by `rotation` quarter turns, so
+    /// that 0, 1, 2 and 3 rotate it by 0°, 90°, 180° and 270° respectively.
+    pub fn deploy_oriented(&mut self, pattern: &str, row: u32, column: u32, rotation: u8) -> bool {
+        self.deploy_transformed(pattern, row, column, rotation, Flip::None)
+    }
+
+    /// Deploy a pattern mirrored by `flip`, then rotated clockwise by
+    /// `rotation` quarter turns.
+    pub fn deploy_transformed(
+        &mut self,
+        pattern: &str,
+        row: u32,
+        column: u32,
+        rotation: u8,
+        flip: Flip,
+    ) -> bool {
+        let Some(offsets) = self.pattern_offsets(pattern) else {
+            return false;
+        };
+
+        let alive_cells = offsets
+            .iter()
+            .map(|&(delta_row, delta_col)| match flip {
+                Flip::None => (delta_row, delta_col),
+                Flip::Horizontal => (delta_row, -delta_col),
+                Flip::Vertical => (-delta_row, delta_col),
+            })
+            .map(|(delta_row, delta_col)| {
+                (0..rotation % 4).fold((delta_row, delta_col), |(delta_row, delta_col), _| {
+                    (delta_col, -delta_row)
+                })
+            })
+            .map(|(delta_row, delta_col)| {
+                let row = (row as i64 + delta_row as i64).rem_euclid(self.height as i64);
+                let col = (column as i64 + delta_col as i64).rem_euclid(self.width as i64);
+                (row as u32, col as u32)
+            })
+            .collect::<Vec<_>>();
+
+        self.push_history();
+        self.insert_cells(&alive_cells);
+        true
+    }
+
+    /// Register a pattern in run length encoded (RLE) format under a name,
+    /// so that it can be deployed like one of the built-in patterns.
+    ///
+    /// Names are case-insensitive, and take precedence over the built-in
+    /// patterns. Unlike the built-in patterns, custom patterns are anchored
+    /// at the top left corner of their bounding box.
+    pub fn register_pattern(&mut self, name: &str, rle: &str) -> Result<(), String> {
+        let offsets = rle::parse(rle)?
+            .cells
+            .into_iter()
+            .map(|(delta_row, delta_col)| (delta_row as i32, delta_col as i32))
+            .collect();
+
+        self.custom_patterns.insert(name.to_uppercase(), offsets);
+        Ok(())
+    }
+
+    /// Whether `deploy` knows a pattern by the given name, either built in or
+    /// registered with `register_pattern`.
+    ///
+    /// Names are case-insensitive.
+    pub fn has_pattern(&self, name: &str) -> bool {
+        self.pattern_offsets(name).is_some()
+    }
+
+    /// Get the smallest rectangle containing the live cells of a pattern as
+    /// the array `[min_row, min_col, max_row, max_col]` of offsets from its
+    /// anchor, so that JS can preview where the pattern will go.
+    ///
+    /// Exported to JS as `pattern_bounds`, in place of the tuple that Rust
+    /// callers get from `pattern_bounds`.
+    #[wasm_bindgen(js_name = pattern_bounds)]
+    pub fn pattern_bounds_array(&self, name: &str) -> Option<Vec<i32>> {
+        let (min_row, min_col, max_row, max_col) = self.pattern_bounds(name)?;
+        Some(vec![min_row, min_col, max_row, max_col])
+    }
+
+    /// Restore the cells and generation to how they were before the last
+    /// tick, toggle, deploy or clear.
+    ///
+    /// Returns false if there is nothing to undo.
+    pub fn undo(&mut self) -> bool {
+        match self.history.pop_back() {
+            Some((cells, generation)) => {
+                let undone = std::mem::replace(&mut self.cells, cells);
+                self.redo_stack.push((undone, self.generation));
+                self.generation = generation;
+                self.forget_ages_and_colors();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Go back to the generation before the last tick, as it was saved in
+    /// the undo history.
+    ///
+    /// Generations advanced by a single `tick_many` are stepped back over
+    /// together. Returns false if there is no earlier generation to go back
+    /// to, including when the cells have been edited since the last tick.
+    /// Nothing stepped back over can be redone.
+    pub fn step_back(&mut self) -> bool {
+        match self.history.back() {
+            Some(&(_, generation)) if generation < self.generation => {
+                let (cells, generation) = self.history.pop_back().unwrap();
+                self.cells = cells;
+                self.generation = generation;
+                self.redo_stack.clear();
+                self.forget_ages_and_colors();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Set how many earlier states are kept for `undo`, discarding the oldest
+    /// ones beyond the new limit.
+    ///
+    /// A limit of 0 disables the undo history altogether.
+    pub fn set_history_limit(&mut self, limit: usize) {
+        self.history_limit = limit;
+        let excess = self.history.len().saturating_sub(limit);
+        self.history.drain(..excess);
+    }
+
+    /// Get the number of earlier states that can currently be undone.
+    pub fn history_len(&self) -> usize {
+        self.history.len()
+    }
+
+    /// Restore the cells and generation to how they were before the last
+    /// undo.
+    ///
+    /// Returns false if there is nothing to redo, including when the cells
+    /// have changed since the last undo.
+    pub fn redo(&mut self) -> bool {
+        match self.redo_stack.pop() {
+            Some((cells, generation)) => {
+                self.save_history();
+                self.cells = cells;
+                self.generation = generation;
+                self.forget_ages_and_colors();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Insert a pattern in run length encoded (RLE) format, with the top
+    /// left corner of its bounding box at the given row and column.
+    ///
+    /// The universe switches to the rule given in the pattern's header, or to
+    /// Conway's Game of Life if it has none or it could not be parsed. Leaves
+    /// the universe unchanged if the pattern could not be parsed.
+    pub fn insert_rle(&mut self, rle: &str, row: u32, column: u32) -> Result<(), String> {
+        let pattern = rle::parse(rle)?;
+        self.push_history();
+        self.set_cells_at(&pattern.cells, row, column);
+        self.set_pattern_rule(pattern.rule.as_deref());
+        Ok(())
+    }
+
+    /// Replace every cell with a pattern in run length encoded (RLE) format,
+    /// centered in the universe, and reset the generation counter.
+    ///
+    /// The universe switches to the rule given in the pattern's header like
+    /// `insert_rle`, and the pattern is what `reset` returns to. Leaves the
+    /// universe unchanged if the pattern could not be parsed.
+    pub fn load_rle(&mut self, rle: &str) -> Result<(), String> {
+        let pattern = rle::parse(rle)?;
+
+        let height = pattern.cells.iter().map(|&(row, _)| row + 1).max();
+        let width = pattern.cells.iter().map(|&(_, col)| col + 1).max();
+
+        self.push_history();
+        self.cells.clear();
+        self.set_cells_at(
+            &pattern.cells,
+            self.height.saturating_sub(height.unwrap_or(0)) / 2,
+            self.width.saturating_sub(width.unwrap_or(0)) / 2,
+        );
+        self.generation = 0;
+        self.forget_ages_and_colors();
+        self.pop_history.clear();
+        self.set_pattern_rule(pattern.rule.as_deref());
+        self.mark_initial();
+        Ok(())
+    }
+
+    /// Get the live cells in run length encoded (RLE) format, cropped to
+    /// the smallest rectangle containing all of them.
+    pub fn to_rle(&self) -> String {
+        let (width, height, offsets) = self.cropped_live_cells();
+        rle::write(width, height, &self.rule.to_string(), &offsets)
+    }
+
+    /// Insert a pattern in Plaintext format, with the top left corner of its
+    /// bounding box at the given row and column.
+    pub fn insert_plaintext(&mut self, text: &str, row: u32, column: u32) {
+        let offsets = plaintext::parse(text);
+        self.push_history();
+        self.set_cells_at(&offsets, row, column);
+    }
+
+    /// Get the live cells in Plaintext format, cropped to the smallest
+    /// rectangle containing all of them.
+    ///
+    /// Returns an empty string if every cell is dead.
+    pub fn to_plaintext(&self) -> String {
+        let (width, height, offsets) = self.cropped_live_cells();
+        plaintext::write(width, height, &offsets)
+    }
+
+    /// Insert a pattern in Life 1.06 format, with its origin at the middle
+    /// of the universe.
+    ///
+    /// Cells that fall outside the universe are skipped rather than wrapped
+    /// around. Leaves the universe unchanged if the pattern could not be
+    /// parsed.
+    pub fn insert_life106(&mut self, text: &str) -> Result<(), String> {
+        let coords = life106::parse(text)?;
+        let (center_row, center_col) = (self.height as i64 / 2, self.width as i64 / 2);
+        let alive_cells = coords
+            .into_iter()
+            .filter_map(|(row, col)| {
+                let row = u32::try_from(center_row + row as i64).ok()?;
+                let col = u32::try_from(center_col + col as i64).ok()?;
+                (row < self.height && col < self.width).then_some((row, col))
+            })
+            .collect::<Vec<_>>();
+
+        self.push_history();
+        self.insert_cells(&alive_cells);
+        Ok(())
+    }
+
+    /// Get the live cells in Life 1.06 format, with coordinates relative to
+    /// the top left corner of the smallest rectangle containing all of them.
+    ///
+    /// Returns just the header if every cell is dead.
+    pub fn to_life106(&self) -> String {
+        let (_, _, offsets) = self.cropped_live_cells();
+        life106::write(&offsets)
+    }
+
+    /// Get the width and height of the universe, followed by the index of
+    /// every live cell, which is compact for sparse universes.
+    pub fn to_sparse(&self) -> Vec<u32> {
+        let mut data = vec![self.width, self.height];
+        data.extend(self.cells.ones().map(|idx| idx as u32));
+        data
+    }
+
+    /// Create a universe from data in the format written by `to_sparse`.
+    ///
+    /// Returns an error if the dimensions are missing, zero or too large, or
+    /// an index is outside the universe.
+    pub fn from_sparse(data: &[u32]) -> Result<Universe, String> {
+        let [width, height, ref live @ ..] = *data else {
+            return Err("missing universe dimensions".to_string());
+        };
+        if width == 0 || height == 0 {
+            return Err("universe dimensions must be nonzero".to_string());
+        }
+        Universe::check_size(width, height)?;
+
+        let mut universe = Universe::dead(width, height);
+        for &idx in live {
+            if idx as usize >= universe.cells.len() {
+                return Err(format!("cell index {} is outside the universe", idx));
+            }
+            universe.cells.insert(idx as usize);
+        }
+        universe.mark_initial();
+        Ok(universe)
+    }
+
+    /// Encode the universe as a URL-safe base64 string of its width and
+    /// height, followed by its cells packed eight to a byte.
+    ///
+    /// This is more compact than the other formats for dense universes.
+    pub fn to_base64(&self) -> String {
+        let mut bytes = Vec::with_capacity(8 + self.cells.len().div_ceil(8));
+        bytes.extend(self.width.to_le_bytes());
+        bytes.extend(self.height.to_le_bytes());
+        bytes.resize(8 + self.cells.len().div_ceil(8), 0);
+        for idx in self.cells.ones() {
+            bytes[8 + idx / 8] |= 1 << (idx % 8);
+        }
+        URL_SAFE_NO_PAD.encode(bytes)
+    }
+
+    /// Create a universe from a string in the format written by
+    /// `to_base64`.
+    ///
+    /// Returns an error for invalid base64, zero or too large dimensions, or
+    /// the wrong number of bytes of cells.
+    pub fn from_base64(encoded: &str) -> Result<Universe, String> {
+        let bytes = URL_SAFE_NO_PAD
+            .decode(encoded)
+            .map_err(|err| err.to_string())?;
+        let (Some(width), Some(height)) = (bytes.get(0..4), bytes.get(4..8)) else {
+            return Err("missing universe dimensions".to_string());
+        };
+        let width = u32::from_le_bytes(width.try_into().unwrap());
+        let height = u32::from_le_bytes(height.try_into().unwrap());
+        if width == 0 || height == 0 {
+            return Err("universe dimensions must be nonzero".to_string());
+        }
+        Universe::check_size(width, height)?;
+
+        let mut universe = Universe::dead(width, height);
+        let cells = &bytes[8..];
+        if cells.len() != universe.cells.len().div_ceil(8) {
+            return Err(format!(
+                "expected {} bytes of cells, found {}",
+                universe.cells.len().div_ceil(8),
+                cells.len()
+            ));
+        }
+        for idx in 0..universe.cells.len() {
+            universe
+                .cells
+                .set(idx, cells[idx / 8] & (1 << (idx % 8)) != 0);
+        }
+        universe.mark_initial();
+        Ok(universe)
+    }
+
+    /// Get the colors of the cells as red, green, blue and alpha bytes in
+    /// row-major order, ready to upload as a WebGL texture, with colors
+    /// given as `0xRRGGBBAA`.
+    ///
+    /// See `to_rgba` for the same from Rust.
+    pub fn rgba_texture(&self, alive: u32, dead: u32) -> Vec<u8> {
+        self.to_rgba(alive.to_be_bytes(), dead.to_be_bytes())
+    }
+
+    /// Draw the universe with the given characters for live and dead cells,
+    /// ending every row with a newline.
+    pub fn render(&self, alive: char, dead: char) -> String {
+        let mut text = String::new();
+        for row in 0..self.height {
+            for col in 0..self.width {
+                text.push(match self.cells[self.get_index(row, col)] {
+                    true => alive,
+                    false => dead,
+                });
+            }
+            text.push('\n');
+        }
+        text
     }
 }
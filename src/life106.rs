@@ -0,0 +1,39 @@
+//! Parsing and writing of patterns in the Life 1.06 format.
+//!
+//! See <https://conwaylife.com/wiki/Life_1.06> for a description of the
+//! format.
+
+/// Parse a Life 1.06 pattern into the `(row, column)` coordinates of its
+/// live cells, which may be negative as they are relative to an origin
+/// usually near the middle of the pattern.
+///
+/// Each line other than `#` header and comment lines holds the `x y`
+/// coordinates of a single live cell.
+pub fn parse(text: &str) -> Result<Vec<(i32, i32)>, String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let coords = line
+                .split_whitespace()
+                .map(str::parse::<i32>)
+                .collect::<Result<Vec<_>, _>>();
+            match coords.as_deref() {
+                Ok(&[x, y]) => Ok((y, x)),
+                _ => Err(format!("invalid coordinates '{}'", line)),
+            }
+        })
+        .collect()
+}
+
+/// Write a pattern in Life 1.06 format, from the `(row, column)` offsets of
+/// its live cells.
+///
+/// Every line, including the `#Life 1.06` header, ends with a newline.
+pub fn write(cells: &[(u32, u32)]) -> String {
+    let mut text = "#Life 1.06\n".to_string();
+    for &(row, col) in cells {
+        text.push_str(&format!("{} {}\n", col, row));
+    }
+    text
+}
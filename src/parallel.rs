@@ -0,0 +1,61 @@
+//! Multithreaded computation of the next generation, enabled by the
+//! `parallel` feature on native targets.
+//!
+//! Every cell's next state depends only on the current cells, so chunks of
+//! rows of the next generation are computed independently with the bitwise
+//! kernel, in SIMD vectors with the `simd` feature, then written into the
+//! bitset one after another.
+
+use fixedbitset::FixedBitSet;
+use rayon::prelude::*;
+
+use crate::bitwise::{self, RowKernel};
+use crate::Universe;
+
+/// The number of cells below which the next generation is computed on the
+/// current thread, as the work isn't worth spreading out.
+pub(crate) const PARALLEL_MIN_CELLS: usize = 128 * 128;
+
+/// The number of rows computed together by each task.
+const ROWS_PER_CHUNK: usize = 32;
+
+/// The word each task adds up neighbors in, the same as on a single thread.
+#[cfg(feature = "simd")]
+type Lanes = wide::u64x4;
+#[cfg(not(feature = "simd"))]
+type Lanes = u64;
+
+impl Universe {
+    /// Write the cells of the next generation into `next`, which must be the
+    /// same length as the current cells.
+    pub(crate) fn write_next_cells_parallel(&self, next: &mut FixedBitSet) {
+        if self.width < 3 {
+            return self.write_next_cells_in::<Lanes>(next);
+        }
+
+        let width = self.width as usize;
+        let height = self.height as usize;
+        let words = width.div_ceil(64);
+
+        let chunks = (0..height.div_ceil(ROWS_PER_CHUNK))
+            .into_par_iter()
+            .map(|chunk| {
+                let mut kernel = RowKernel::<Lanes>::new(self);
+                let rows = chunk * ROWS_PER_CHUNK..height.min((chunk + 1) * ROWS_PER_CHUNK);
+                let mut next_rows = Vec::with_capacity(rows.len() * words);
+                for row in rows {
+                    next_rows.extend_from_slice(kernel.next_row(row));
+                }
+                next_rows
+            })
+            .collect::<Vec<_>>();
+
+        for (row, next_row) in chunks
+            .iter()
+            .flat_map(|chunk| chunk.chunks(words))
+            .enumerate()
+        {
+            bitwise::write_bits(next.as_mut_slice(), row * width, width, next_row);
+        }
+    }
+}
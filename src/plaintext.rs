@@ -0,0 +1,39 @@
+//! Parsing and writing of patterns in the Plaintext (`.cells`) format.
+//!
+//! See <https://conwaylife.com/wiki/Plaintext> for a description of the
+//! format.
+
+/// Parse a Plaintext pattern into the `(row, column)` offsets of its live
+/// cells, relative to the top left corner of its bounding box.
+///
+/// Lines starting with `!` are comments. Every `O` (or `o`) is a live cell
+/// and any other character is dead; shorter lines are padded with dead cells
+/// on the right.
+pub fn parse(text: &str) -> Vec<(u32, u32)> {
+    text.lines()
+        .filter(|line| !line.starts_with('!'))
+        .enumerate()
+        .flat_map(|(row, line)| {
+            line.chars()
+                .enumerate()
+                .filter(|(_, cell)| matches!(cell, 'O' | 'o'))
+                .map(move |(col, _)| (row as u32, col as u32))
+        })
+        .collect()
+}
+
+/// Write a pattern of the given size in Plaintext format, from the
+/// `(row, column)` offsets of its live cells in row-major order.
+///
+/// Rows are separated by newlines, without a trailing newline.
+pub fn write(width: u32, height: u32, cells: &[(u32, u32)]) -> String {
+    let mut rows = vec![vec!['.'; width as usize]; height as usize];
+    for &(row, col) in cells {
+        rows[row as usize][col as usize] = 'O';
+    }
+
+    rows.iter()
+        .map(|row| row.iter().collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,144 @@
+//! Parsing and writing of patterns in the run length encoded (RLE) format.
+//!
+//! See <https://conwaylife.com/wiki/Run_Length_Encoded> for a description of
+//! the format.
+
+/// A pattern parsed from RLE.
+pub struct Pattern {
+    /// The `(row, column)` offsets of the live cells, relative to the top
+    /// left corner of the pattern's bounding box.
+    pub cells: Vec<(u32, u32)>,
+    /// The rulestring from the header, if it had one.
+    pub rule: Option<String>,
+}
+
+/// Parse an RLE pattern into the offsets of its live cells and the rule it
+/// runs under.
+///
+/// Comment lines starting with `#` are skipped, and the `x = .., y = ..`
+/// header line is optional.
+pub fn parse(rle: &str) -> Result<Pattern, String> {
+    let mut lines = rle
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .peekable();
+
+    let rule = match lines.next_if(|line| line.starts_with('x')) {
+        Some(header) => parse_header(header)?,
+        None => None,
+    };
+
+    let mut cells = Vec::new();
+    let (mut row, mut col) = (0u32, 0u32);
+    let mut run: Option<u32> = None;
+
+    for line in lines {
+        for token in line.chars() {
+            match token {
+                '0'..='9' => {
+                    let digit = token.to_digit(10).unwrap_or_default();
+                    let count = run
+                        .unwrap_or(0)
+                        .checked_mul(10)
+                        .and_then(|n| n.checked_add(digit));
+                    run = Some(count.ok_or("RLE run count is too large")?);
+                }
+                'b' | 'o' => {
+                    let count = run.take().unwrap_or(1);
+                    let end = col.checked_add(count).ok_or("RLE row is too long")?;
+                    if token == 'o' {
+                        cells.extend((col..end).map(|col| (row, col)));
+                    }
+                    col = end;
+                }
+                '$' => {
+                    let count = run.take().unwrap_or(1);
+                    row = row.checked_add(count).ok_or("RLE pattern is too tall")?;
+                    col = 0;
+                }
+                '!' => {
+                    if run.is_some() {
+                        return Err("RLE run count is not followed by a tag".to_string());
+                    }
+                    return Ok(Pattern { cells, rule });
+                }
+                token if token.is_whitespace() => {}
+                token => return Err(format!("unexpected character '{}' in RLE body", token)),
+            }
+        }
+    }
+
+    Err("RLE body is missing the terminating '!'".to_string())
+}
+
+/// Write a pattern of the given size in RLE format, from the `(row, column)`
+/// offsets of its live cells in row-major order.
+pub fn write(width: u32, height: u32, rule: &str, cells: &[(u32, u32)]) -> String {
+    // Each tag with its run count, merging consecutive runs of the same tag.
+    let mut runs: Vec<(u32, char)> = Vec::new();
+    let mut push = |count: u32, tag: char| match runs.last_mut() {
+        Some((last_count, last_tag)) if *last_tag == tag => *last_count += count,
+        _ => runs.push((count, tag)),
+    };
+
+    let (mut row, mut col) = (0, 0);
+    for &(cell_row, cell_col) in cells {
+        if cell_row > row {
+            push(cell_row - row, '$');
+            row = cell_row;
+            col = 0;
+        }
+        if cell_col > col {
+            push(cell_col - col, 'b');
+        }
+        push(1, 'o');
+        col = cell_col + 1;
+    }
+    push(1, '!');
+
+    let mut rle = format!("x = {}, y = {}, rule = {}\n", width, height, rule);
+    let mut line_length = 0;
+    for (count, tag) in runs {
+        let run = match count {
+            1 => tag.to_string(),
+            _ => format!("{}{}", count, tag),
+        };
+
+        // Lines in RLE files should not exceed 70 characters.
+        if line_length + run.len() > MAX_LINE_LENGTH {
+            rle.push('\n');
+            line_length = 0;
+        }
+        line_length += run.len();
+        rle.push_str(&run);
+    }
+
+    rle
+}
+
+const MAX_LINE_LENGTH: usize = 70;
+
+/// Validate an `x = .., y = ..` RLE header line, getting the value of its
+/// `rule` field if it has one.
+fn parse_header(header: &str) -> Result<Option<String>, String> {
+    let mut rule = None;
+    for field in header.split(',') {
+        let (key, value) = field
+            .split_once('=')
+            .ok_or_else(|| format!("malformed RLE header field '{}'", field.trim()))?;
+
+        match key.trim() {
+            "x" | "y" => {
+                value
+                    .trim()
+                    .parse::<u32>()
+                    .map_err(|_| format!("malformed RLE header dimension '{}'", value.trim()))?;
+            }
+            "rule" => rule = Some(value.trim().to_string()),
+            _ => {}
+        }
+    }
+
+    Ok(rule)
+}
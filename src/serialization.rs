@@ -0,0 +1,127 @@
+//! `serde` support for `Universe`, enabled by the `serde` feature.
+//!
+//! A universe is serialized as its dimensions, generation and rulestring,
+//! along with the indices of its live cells, which keeps sparse universes
+//! small.
+//!
+//! `Universe::to_json` uses a separate, human-readable format for saving
+//! universes from JS.
+
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use wasm_bindgen::prelude::*;
+
+use crate::{Rule, Universe};
+
+#[derive(Serialize, Deserialize)]
+struct UniverseState {
+    width: u32,
+    height: u32,
+    generation: u64,
+    rule: String,
+    live: Vec<usize>,
+}
+
+impl Serialize for Universe {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        UniverseState {
+            width: self.width,
+            height: self.height,
+            generation: self.generation,
+            rule: self.rule.to_string(),
+            live: self.cells.ones().collect(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Universe {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let state = UniverseState::deserialize(deserializer)?;
+        if state.width == 0 || state.height == 0 {
+            return Err(D::Error::custom("universe dimensions must be nonzero"));
+        }
+        Universe::check_size(state.width, state.height).map_err(D::Error::custom)?;
+
+        let mut universe = Universe::dead(state.width, state.height);
+        universe.generation = state.generation;
+        universe.rule = state
+            .rule
+            .parse::<Rule>()
+            .map_err(|_| D::Error::custom(format!("invalid rulestring '{}'", state.rule)))?;
+
+        for idx in state.live {
+            if idx >= universe.cells.len() {
+                return Err(D::Error::custom(format!(
+                    "live cell {} is out of range",
+                    idx
+                )));
+            }
+            universe.cells.insert(idx);
+        }
+
+        universe.mark_initial();
+        Ok(universe)
+    }
+}
+
+/// The JSON save format: an object with the `width`, `height`, `generation`
+/// and `rule` of the universe, and a `live` array of `[row, col]` pairs.
+#[derive(Serialize, Deserialize)]
+struct JsonUniverse {
+    width: u32,
+    height: u32,
+    generation: u64,
+    rule: String,
+    live: Vec<(u32, u32)>,
+}
+
+#[wasm_bindgen]
+impl Universe {
+    /// Get the universe as a JSON object with its `width`, `height`,
+    /// `generation` and `rule`, and a `live` array of the `[row, col]` pairs
+    /// of its live cells.
+    pub fn to_json(&self) -> String {
+        let json = JsonUniverse {
+            width: self.width,
+            height: self.height,
+            generation: self.generation,
+            rule: self.rule.to_string(),
+            live: self.live_cells().collect(),
+        };
+
+        serde_json::to_string(&json).unwrap_or_default()
+    }
+
+    /// Create a universe from a JSON object in the format written by
+    /// `to_json`.
+    ///
+    /// Returns an error for malformed JSON, zero or too large dimensions, an
+    /// invalid rulestring or live cells outside the universe.
+    pub fn from_json(json: &str) -> Result<Universe, String> {
+        let json: JsonUniverse = serde_json::from_str(json).map_err(|err| err.to_string())?;
+        if json.width == 0 || json.height == 0 {
+            return Err("universe dimensions must be nonzero".to_string());
+        }
+        Universe::check_size(json.width, json.height)?;
+
+        let mut universe = Universe::dead(json.width, json.height);
+        universe.generation = json.generation;
+        universe.rule = json
+            .rule
+            .parse::<Rule>()
+            .map_err(|_| format!("invalid rulestring '{}'", json.rule))?;
+
+        for (row, col) in json.live {
+            if row >= universe.height || col >= universe.width {
+                return Err(format!("live cell ({}, {}) is out of range", row, col));
+            }
+            let idx = universe.get_index(row, col);
+            universe.cells.insert(idx);
+        }
+
+        universe.mark_initial();
+        Ok(universe)
+    }
+}
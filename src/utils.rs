@@ -8,3 +8,63 @@ pub fn set_panic_hook() {
     #[cfg(feature = "console_error_panic_hook")]
     console_error_panic_hook::set_once();
 }
+
+/// A small xorshift64* pseudo-random number generator.
+///
+/// Used instead of `js_sys::Math::random` when a universe has to be
+/// reproducible from a seed.
+pub struct XorShift64 {
+    state: u64,
+}
+
+impl XorShift64 {
+    pub fn new(seed: u64) -> XorShift64 {
+        // Run the seed through a splitmix64 step so that small or similar
+        // seeds still produce well-mixed states. Xorshift must never be
+        // seeded with zero, as it would stay at zero forever.
+        let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+
+        XorShift64 {
+            state: if z == 0 { 1 } else { z },
+        }
+    }
+
+    /// Get the next value, uniformly distributed in `[0, 1)`.
+    pub fn next_f64(&mut self) -> f64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        let x = self.state.wrapping_mul(0x2545_F491_4F6C_DD1D);
+        (x >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// A 64-bit FNV-1a hasher.
+///
+/// Unlike the standard library's default hasher, its output is the same in
+/// every build and on every platform, so hashes can be stored and compared.
+pub struct Fnv64 {
+    state: u64,
+}
+
+impl Fnv64 {
+    pub fn new() -> Fnv64 {
+        Fnv64 {
+            state: 0xCBF2_9CE4_8422_2325,
+        }
+    }
+
+    pub fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.state ^= byte as u64;
+            self.state = self.state.wrapping_mul(0x0000_0100_0000_01B3);
+        }
+    }
+
+    pub fn finish(&self) -> u64 {
+        self.state
+    }
+}
@@ -0,0 +1,125 @@
+//! Test suite for native targets, covering what can't run in a browser.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+extern crate wasm_game_of_life;
+use wasm_game_of_life::Universe;
+
+// `Universe::with_size` logs to the JS console, which isn't there natively.
+#[cfg(test)]
+pub fn empty_universe(width: usize, height: usize) -> Universe {
+    Universe::from_bool_grid(&vec![vec![false; width]; height]).unwrap()
+}
+
+#[cfg(test)]
+fn naive_tick(grid: &[Vec<bool>]) -> Vec<Vec<bool>> {
+    let (width, height) = (grid[0].len(), grid.len());
+    (0..height)
+        .map(|row| {
+            (0..width)
+                .map(|col| {
+                    let mut live_neighbors = 0;
+                    for delta_row in [height - 1, 0, 1] {
+                        for delta_col in [width - 1, 0, 1] {
+                            if (delta_row, delta_col) != (0, 0) {
+                                live_neighbors += grid[(row + delta_row) % height]
+                                    [(col + delta_col) % width]
+                                    as u8;
+                            }
+                        }
+                    }
+                    live_neighbors == 3 || (grid[row][col] && live_neighbors == 2)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[test]
+pub fn test_gosper_gun_matches_naive_tick() {
+    // Big enough for every generation to be computed on multiple threads
+    // when the `parallel` feature is enabled.
+    let mut universe = empty_universe(128, 128);
+    universe.deploy("gosper", 10, 10);
+
+    for _ in 0..50 {
+        let expected = naive_tick(&universe.to_bool_grid());
+
+        // Start over from the current cells, so that the next generation is
+        // computed in full rather than only near the last changes.
+        universe = Universe::from_bool_grid(&universe.to_bool_grid()).unwrap();
+        universe.tick();
+        assert_eq!(universe.to_bool_grid(), expected);
+    }
+}
+
+#[test]
+pub fn test_simd_matches_scalar() {
+    // Pseudo-random cells, on rows that don't fill a whole number of words.
+    let mut state = 0x2545_F491_4F6C_DD1Du64;
+    let grid = (0..100)
+        .map(|_| {
+            (0..300)
+                .map(|_| {
+                    state ^= state << 13;
+                    state ^= state >> 7;
+                    state ^= state << 17;
+                    state.is_multiple_of(3)
+                })
+                .collect()
+        })
+        .collect::<Vec<Vec<bool>>>();
+    let mut universe = Universe::from_bool_grid(&grid).unwrap();
+
+    for _ in 0..100 {
+        let expected = universe.next_cells_scalar();
+        #[cfg(feature = "simd")]
+        assert_eq!(universe.next_cells_simd(), expected);
+        universe.tick();
+        assert_eq!(universe.get_cells(), &expected);
+    }
+}
+
+#[test]
+pub fn test_run_with() {
+    let mut universe = empty_universe(16, 16);
+    universe.deploy("glider", 4, 4);
+
+    let mut calls = Vec::new();
+    universe.run_with(5, |generation, population| {
+        calls.push((generation, population))
+    });
+    assert_eq!(calls, [(1, 5), (2, 5), (3, 5), (4, 5), (5, 5)]);
+    assert_eq!(universe.generation(), 5);
+}
+
+#[test]
+pub fn test_tick_with() {
+    let mut universe = empty_universe(32, 32);
+    universe.deploy("acorn", 16, 16);
+    let mut expected_universe = empty_universe(32, 32);
+    expected_universe.deploy("acorn", 16, 16);
+
+    for _ in 0..20 {
+        universe.tick_with(|alive, live_neighbors| {
+            live_neighbors == 3 || (alive && live_neighbors == 2)
+        });
+        expected_universe.tick();
+        assert_eq!(universe, expected_universe);
+    }
+    assert_eq!(universe.generation(), 20);
+
+    // The universe's own rule carries on from a custom generation.
+    let inverted = universe
+        .to_bool_grid()
+        .iter()
+        .map(|row| row.iter().map(|&alive| !alive).collect())
+        .collect::<Vec<_>>();
+    let mut expected_universe = Universe::from_bool_grid(&inverted).unwrap();
+    universe.tick_with(|alive, _| !alive);
+    assert_eq!(universe, expected_universe);
+
+    universe.tick();
+    expected_universe.tick();
+    assert_eq!(universe, expected_universe);
+}
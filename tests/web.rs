@@ -6,7 +6,7 @@ extern crate wasm_bindgen_test;
 use wasm_bindgen_test::*;
 
 extern crate wasm_game_of_life;
-use wasm_game_of_life::Universe;
+use wasm_game_of_life::{available_patterns, diff, Axis, Boundary, Flip, Snapshot, Universe};
 
 wasm_bindgen_test_configure!(run_in_browser);
 
@@ -17,7 +17,7 @@ fn pass() {
 
 #[cfg(test)]
 pub fn input_spaceship() -> Universe {
-    let mut universe = Universe::new();
+    let mut universe = Universe::new(None);
     universe.set_width(6);
     universe.set_height(6);
     universe.set_cells(&[(1, 2), (2, 3), (3, 1), (3, 2), (3, 3)]);
@@ -26,7 +26,7 @@ pub fn input_spaceship() -> Universe {
 
 #[cfg(test)]
 pub fn expected_spaceship() -> Universe {
-    let mut universe = Universe::new();
+    let mut universe = Universe::new(None);
     universe.set_width(6);
     universe.set_height(6);
     universe.set_cells(&[(2, 1), (2, 3), (3, 2), (3, 3), (4, 2)]);
@@ -46,3 +46,2396 @@ pub fn test_tick() {
     input_universe.tick();
     assert_eq!(&input_universe.get_cells(), &expected_universe.get_cells());
 }
+
+#[wasm_bindgen_test]
+pub fn test_new_seeded() {
+    // The same seed and chance of life should always give the same cells.
+    let first_universe = Universe::new_seeded(42, Some(0.5));
+    let second_universe = Universe::new_seeded(42, Some(0.5));
+    assert_eq!(first_universe.get_cells(), second_universe.get_cells());
+
+    // A different seed should give a different layout.
+    let other_universe = Universe::new_seeded(43, Some(0.5));
+    assert_ne!(first_universe.get_cells(), other_universe.get_cells());
+}
+
+#[wasm_bindgen_test]
+pub fn test_with_size() {
+    let universe = Universe::with_size(10, 20, None).unwrap();
+    assert!(universe.width() == 10 && universe.height() == 20);
+    assert_eq!(universe.get_cells().len(), 10 * 20);
+
+    // Zero dimensions are clamped rather than producing an empty universe.
+    let universe = Universe::with_size(0, 0, None).unwrap();
+    assert!(universe.width() == 1 && universe.height() == 1);
+}
+
+#[wasm_bindgen_test]
+pub fn test_default() {
+    let mut universe = Universe::default();
+    assert_eq!((universe.width(), universe.height()), (256, 256));
+    assert_eq!(universe.population(), 0);
+
+    universe.set_width(4);
+    assert_eq!((universe.width(), universe.height()), (4, 256));
+    universe.tick();
+    assert_eq!(universe.generation(), 1);
+}
+
+#[cfg(test)]
+pub fn empty_universe(width: u32, height: u32) -> Universe {
+    Universe::with_size(width, height, Some(0.0)).unwrap()
+}
+
+#[wasm_bindgen_test]
+pub fn test_boundary() {
+    // A horizontal blinker in the top-left corner.
+    let blinker = [(0, 0), (0, 1), (0, 2)];
+
+    // Wrapping around, the blinker turns vertical through the bottom edge.
+    let mut toroidal_universe = empty_universe(5, 5);
+    toroidal_universe.set_cells(&blinker);
+    toroidal_universe.tick();
+
+    let mut expected_universe = empty_universe(5, 5);
+    expected_universe.set_cells(&[(4, 1), (0, 1), (1, 1)]);
+    assert_eq!(toroidal_universe.get_cells(), expected_universe.get_cells());
+
+    // With dead edges, the cell that would be born above the grid is lost.
+    let mut dead_universe = empty_universe(5, 5);
+    dead_universe.set_boundary(Boundary::Dead);
+    dead_universe.set_cells(&blinker);
+    dead_universe.tick();
+
+    let mut expected_universe = empty_universe(5, 5);
+    expected_universe.set_cells(&[(0, 1), (1, 1)]);
+    assert_eq!(dead_universe.get_cells(), expected_universe.get_cells());
+}
+
+#[wasm_bindgen_test]
+pub fn test_reflective_boundary() {
+    // A lone cell in the corner is reflected onto itself across both
+    // edges and the corner, giving it three live neighbors.
+    let mut universe = empty_universe(5, 5);
+    universe.set_boundary(Boundary::Reflective);
+    universe.set_cells(&[(0, 0)]);
+    universe.tick();
+
+    let mut expected_universe = empty_universe(5, 5);
+    expected_universe.set_cells(&[(0, 0)]);
+    assert_eq!(universe.get_cells(), expected_universe.get_cells());
+
+    // Wrapping around, the same cell has no neighbors and dies.
+    let mut universe = empty_universe(5, 5);
+    universe.set_cells(&[(0, 0)]);
+    universe.tick();
+    assert_eq!(universe.get_cells().count_ones(..), 0);
+}
+
+#[wasm_bindgen_test]
+pub fn test_set_rule_conway() {
+    let mut input_universe = input_spaceship();
+    assert!(input_universe.set_rule("B3/S23"));
+
+    let expected_universe = expected_spaceship();
+
+    input_universe.tick();
+    assert_eq!(&input_universe.get_cells(), &expected_universe.get_cells());
+}
+
+#[wasm_bindgen_test]
+pub fn test_set_rule_highlife() {
+    // A dead cell with six live neighbors.
+    let neighbors = [(1, 1), (1, 2), (1, 3), (3, 1), (3, 2), (3, 3)];
+
+    let mut conway_universe = empty_universe(5, 5);
+    conway_universe.set_cells(&neighbors);
+    conway_universe.tick();
+    assert!(!conway_universe.get_cells()[2 * 5 + 2]);
+
+    let mut highlife_universe = empty_universe(5, 5);
+    assert!(highlife_universe.set_rule("B36/S23"));
+    highlife_universe.set_cells(&neighbors);
+    highlife_universe.tick();
+    assert!(highlife_universe.get_cells()[2 * 5 + 2]);
+
+    // Invalid rulestrings are rejected.
+    assert!(!highlife_universe.set_rule("B9/S23"));
+    assert!(!highlife_universe.set_rule("nonsense"));
+}
+
+#[cfg(test)]
+const REPLICATOR: [(u32, u32); 12] = [
+    (0, 2),
+    (0, 3),
+    (0, 4),
+    (1, 1),
+    (1, 4),
+    (2, 0),
+    (2, 4),
+    (3, 0),
+    (3, 3),
+    (4, 0),
+    (4, 1),
+    (4, 2),
+];
+
+#[wasm_bindgen_test]
+pub fn test_set_ruleset_highlife() {
+    let offset = |(delta_row, delta_col): (i32, i32)| {
+        REPLICATOR
+            .iter()
+            .map(|(row, col)| ((10 + row) as i32 + delta_row, (10 + col) as i32 + delta_col))
+            .map(|(row, col)| (row as u32, col as u32))
+            .collect::<Vec<_>>()
+    };
+
+    let mut universe = empty_universe(32, 32);
+    assert!(universe.set_ruleset("highlife"));
+    universe.set_cells(&offset((0, 0)));
+
+    // After 12 generations, the replicator has copied itself diagonally.
+    for _ in 0..12 {
+        universe.tick();
+    }
+
+    let mut expected_universe = empty_universe(32, 32);
+    expected_universe.set_cells(&offset((-2, -2)));
+    expected_universe.set_cells(&offset((2, 2)));
+    assert_eq!(universe.get_cells(), expected_universe.get_cells());
+}
+
+#[wasm_bindgen_test]
+pub fn test_set_ruleset_seeds() {
+    let mut universe = empty_universe(16, 16);
+    assert!(universe.set_ruleset("Seeds"));
+    assert!(!universe.set_ruleset("nonsense"));
+
+    let alive_cells = [(4, 4), (4, 5), (5, 4), (5, 5), (8, 8), (8, 9)];
+    universe.set_cells(&alive_cells);
+    universe.tick();
+
+    // No cell survives under Seeds, though new cells are born.
+    for (row, col) in alive_cells.iter() {
+        assert!(!universe.get_cells()[(row * 16 + col) as usize]);
+    }
+    assert!(universe.get_cells().count_ones(..) > 0);
+}
+
+#[wasm_bindgen_test]
+pub fn test_insert_rle() {
+    // The glider in the same phase as the built-in pattern.
+    let mut rle_universe = empty_universe(16, 16);
+    rle_universe.insert_rle("2bo$obo$b2o!", 4, 4).unwrap();
+
+    let mut deployed_universe = empty_universe(16, 16);
+    deployed_universe.deploy("glider", 5, 5);
+    assert_eq!(rle_universe.get_cells(), deployed_universe.get_cells());
+
+    // The canonical glider reaches the built-in phase after two generations.
+    let mut rle_universe = empty_universe(16, 16);
+    rle_universe
+        .insert_rle(
+            "#N Glider\n#C The smallest spaceship.\nx = 3, y = 3, rule = B3/S23\nbob$2bo$3o!",
+            3,
+            4,
+        )
+        .unwrap();
+    rle_universe.tick();
+    rle_universe.tick();
+    assert_eq!(rle_universe.get_cells(), deployed_universe.get_cells());
+}
+
+#[wasm_bindgen_test]
+pub fn test_insert_rle_malformed() {
+    let mut universe = empty_universe(16, 16);
+    assert!(universe.insert_rle("bob$2bo$3q!", 0, 0).is_err());
+    assert!(universe.insert_rle("bob$2bo$3o", 0, 0).is_err());
+    assert!(universe
+        .insert_rle("x = three, y = 3\nbob$2bo$3o!", 0, 0)
+        .is_err());
+    assert_eq!(universe.get_cells().count_ones(..), 0);
+}
+
+#[wasm_bindgen_test]
+pub fn test_to_rle() {
+    let mut universe = empty_universe(32, 32);
+    assert_eq!(universe.to_rle(), "x = 0, y = 0, rule = B3/S23\n!");
+
+    universe.deploy("pulsar", 16, 16);
+    let rle = universe.to_rle();
+    assert!(rle.starts_with("x = 13, y = 13, rule = B3/S23\n"));
+
+    // The pulsar spans offsets -6 to 6 around its anchor.
+    let mut imported_universe = empty_universe(32, 32);
+    imported_universe.insert_rle(&rle, 10, 10).unwrap();
+    assert_eq!(imported_universe.get_cells(), universe.get_cells());
+}
+
+#[wasm_bindgen_test]
+pub fn test_insert_plaintext() {
+    let mut universe = empty_universe(8, 8);
+    universe.insert_plaintext("!Name: Blinker\n!\n.O\n.O.\n.O", 2, 3);
+
+    let mut expected_universe = empty_universe(8, 8);
+    expected_universe.set_cells(&[(2, 4), (3, 4), (4, 4)]);
+    assert_eq!(universe.get_cells(), expected_universe.get_cells());
+}
+
+#[wasm_bindgen_test]
+pub fn test_to_plaintext() {
+    let mut universe = empty_universe(8, 8);
+    assert_eq!(universe.to_plaintext(), "");
+
+    universe.deploy("glider", 4, 4);
+    let text = universe.to_plaintext();
+    assert_eq!(text, "..O\nO.O\n.OO");
+
+    let mut imported_universe = empty_universe(8, 8);
+    imported_universe.insert_plaintext(&text, 3, 3);
+    assert_eq!(imported_universe.get_cells(), universe.get_cells());
+}
+
+#[wasm_bindgen_test]
+pub fn test_generation() {
+    let mut universe = input_spaceship();
+    assert_eq!(universe.generation(), 0);
+
+    for _ in 0..5 {
+        universe.tick();
+    }
+    assert_eq!(universe.generation(), 5);
+
+    universe.reset_generation();
+    assert_eq!(universe.generation(), 0);
+
+    universe.tick();
+    universe.clear();
+    assert_eq!(universe.generation(), 0);
+}
+
+#[wasm_bindgen_test]
+pub fn test_population() {
+    let mut universe = empty_universe(16, 16);
+    assert_eq!(universe.population(), 0);
+
+    universe.deploy("glider", 8, 8);
+    assert_eq!(universe.population(), 5);
+
+    // The glider spans rows and columns 7 to 9.
+    assert_eq!(universe.population_in_rect(7, 7, 9, 9), 5);
+    assert_eq!(universe.population_in_rect(9, 9, 7, 7), 5);
+    assert_eq!(universe.population_in_rect(8, 0, 8, 15), 2);
+    assert_eq!(universe.population_in_rect(0, 0, 6, 15), 0);
+}
+
+#[wasm_bindgen_test]
+pub fn test_is_extinct_and_stable() {
+    let universe = empty_universe(8, 8);
+    assert!(universe.is_extinct());
+    assert!(universe.is_stable());
+
+    let mut block_universe = empty_universe(8, 8);
+    block_universe.set_cells(&[(3, 3), (3, 4), (4, 3), (4, 4)]);
+    assert!(!block_universe.is_extinct());
+    assert!(block_universe.is_stable());
+
+    let mut blinker_universe = empty_universe(8, 8);
+    blinker_universe.set_cells(&[(4, 3), (4, 4), (4, 5)]);
+    let cells = blinker_universe.get_cells().clone();
+    assert!(!blinker_universe.is_stable());
+
+    // Checking for stability does not advance the universe.
+    assert_eq!(blinker_universe.get_cells(), &cells);
+    assert_eq!(blinker_universe.generation(), 0);
+}
+
+#[wasm_bindgen_test]
+pub fn test_detect_period() {
+    let mut blinker_universe = empty_universe(16, 16);
+    blinker_universe.set_cells(&[(8, 7), (8, 8), (8, 9)]);
+    let cells = blinker_universe.get_cells().clone();
+    assert_eq!(blinker_universe.detect_period(10), Some(2));
+
+    // The universe is restored afterwards.
+    assert_eq!(blinker_universe.get_cells(), &cells);
+    assert_eq!(blinker_universe.generation(), 0);
+
+    let mut block_universe = empty_universe(16, 16);
+    block_universe.set_cells(&[(3, 3), (3, 4), (4, 3), (4, 4)]);
+    assert_eq!(block_universe.detect_period(10), Some(1));
+
+    // A glider translates rather than oscillating in place.
+    let mut glider_universe = empty_universe(16, 16);
+    glider_universe.deploy("glider", 8, 8);
+    assert_eq!(glider_universe.detect_period(10), None);
+}
+
+#[wasm_bindgen_test]
+pub fn test_undo() {
+    let mut universe = empty_universe(8, 8);
+    assert!(!universe.undo());
+
+    universe.toggle_cell(3, 4);
+    assert!(universe.get_cells()[3 * 8 + 4]);
+
+    assert!(universe.undo());
+    assert!(!universe.get_cells()[3 * 8 + 4]);
+    assert!(!universe.undo());
+
+    // Ticks, deploys and clears can be undone one by one.
+    universe.deploy("glider", 4, 4);
+    let deployed_cells = universe.get_cells().clone();
+    universe.tick();
+    universe.clear();
+
+    assert!(universe.undo());
+    assert_ne!(universe.get_cells(), &deployed_cells);
+    assert!(universe.undo());
+    assert_eq!(universe.get_cells(), &deployed_cells);
+    assert!(universe.undo());
+    assert_eq!(universe.population(), 0);
+
+    // Inserting a pattern in any format is undone separately from the edit
+    // before it.
+    universe.toggle_cell(0, 0);
+    assert!(universe.insert_rle("3o!", 4, 4).is_ok());
+    universe.insert_plaintext("OO", 6, 4);
+    assert!(universe.insert_life106("#Life 1.06\n-2 -2\n").is_ok());
+    assert_eq!(universe.population(), 7);
+    assert!(universe.undo());
+    assert_eq!(universe.population(), 6);
+    assert!(universe.undo());
+    assert_eq!(universe.population(), 4);
+    assert!(universe.undo());
+    assert_eq!(universe.population(), 1);
+    assert!(universe.get_cell(0, 0));
+}
+
+#[wasm_bindgen_test]
+pub fn test_redo() {
+    let mut universe = empty_universe(8, 8);
+    assert!(!universe.redo());
+
+    universe.toggle_cell(3, 4);
+    universe.undo();
+    assert!(universe.redo());
+    assert!(universe.get_cells()[3 * 8 + 4]);
+    assert!(!universe.redo());
+
+    // Walking back and forth through the history is consistent.
+    universe.toggle_cell(5, 5);
+    let cells = universe.get_cells().clone();
+    for _ in 0..3 {
+        assert!(universe.undo());
+        assert!(universe.undo());
+        assert_eq!(universe.population(), 0);
+        assert!(universe.redo());
+        assert!(universe.redo());
+        assert_eq!(universe.get_cells(), &cells);
+    }
+
+    // A fresh edit after undoing discards what could have been redone.
+    universe.undo();
+    universe.toggle_cell(0, 0);
+    assert!(!universe.redo());
+}
+
+#[wasm_bindgen_test]
+pub fn test_resize() {
+    let mut universe = empty_universe(8, 8);
+    universe.deploy("glider", 4, 4);
+    let glider = [(3, 5), (4, 3), (4, 5), (5, 4), (5, 5)];
+
+    universe.resize(32, 16).unwrap();
+    assert!(universe.width() == 32 && universe.height() == 16);
+
+    let mut expected_universe = empty_universe(32, 16);
+    expected_universe.set_cells(&glider);
+    assert_eq!(universe.get_cells(), expected_universe.get_cells());
+
+    // Shrinking drops the cells that no longer fit.
+    universe.resize(5, 5).unwrap();
+    let mut expected_universe = empty_universe(5, 5);
+    expected_universe.set_cells(&[(4, 3)]);
+    assert_eq!(universe.get_cells(), expected_universe.get_cells());
+}
+
+#[wasm_bindgen_test]
+pub fn test_deploy_blinker() {
+    let mut universe = empty_universe(8, 8);
+    universe.deploy("blinker", 4, 4);
+
+    let mut horizontal_universe = empty_universe(8, 8);
+    horizontal_universe.set_cells(&[(4, 3), (4, 4), (4, 5)]);
+    assert_eq!(universe.get_cells(), horizontal_universe.get_cells());
+
+    let mut vertical_universe = empty_universe(8, 8);
+    vertical_universe.set_cells(&[(3, 4), (4, 4), (5, 4)]);
+
+    universe.tick();
+    assert_eq!(universe.get_cells(), vertical_universe.get_cells());
+    universe.tick();
+    assert_eq!(universe.get_cells(), horizontal_universe.get_cells());
+}
+
+#[wasm_bindgen_test]
+pub fn test_deploy_toad() {
+    let mut universe = empty_universe(8, 8);
+    universe.deploy("toad", 4, 4);
+    assert_eq!(universe.population(), 6);
+    assert_eq!(universe.detect_period(10), Some(2));
+
+    let cells = universe.get_cells().clone();
+    universe.tick();
+    assert_ne!(universe.get_cells(), &cells);
+    universe.tick();
+    assert_eq!(universe.get_cells(), &cells);
+}
+
+#[wasm_bindgen_test]
+pub fn test_deploy_beacon() {
+    let mut universe = empty_universe(8, 8);
+    universe.deploy("beacon", 4, 4);
+    assert_eq!(universe.population(), 8);
+
+    // The two innermost corners of the blocks blink off and on.
+    universe.tick();
+    assert_eq!(universe.population(), 6);
+    assert!(!universe.get_cells()[3 * 8 + 3]);
+    assert!(!universe.get_cells()[4 * 8 + 4]);
+
+    universe.tick();
+    assert_eq!(universe.population(), 8);
+    assert!(universe.get_cells()[3 * 8 + 3]);
+    assert!(universe.get_cells()[4 * 8 + 4]);
+}
+
+#[wasm_bindgen_test]
+pub fn test_deploy_wraps_around_edges() {
+    let mut universe = empty_universe(7, 7);
+    universe.deploy("beacon", 0, 0);
+
+    let mut expected_universe = empty_universe(7, 7);
+    expected_universe.set_cells(&[
+        (5, 5),
+        (5, 6),
+        (6, 5),
+        (6, 6),
+        (0, 0),
+        (0, 1),
+        (1, 0),
+        (1, 1),
+    ]);
+    assert_eq!(universe.get_cells(), expected_universe.get_cells());
+
+    assert_eq!(universe.detect_period(10), Some(2));
+}
+
+#[wasm_bindgen_test]
+pub fn test_deploy_pentadecathlon() {
+    let mut universe = empty_universe(32, 32);
+    universe.deploy("pentadecathlon", 16, 16);
+    assert_eq!(universe.population(), 12);
+
+    let cells = universe.get_cells().clone();
+    let mut populations = Vec::new();
+    for _ in 0..15 {
+        universe.tick();
+        populations.push(universe.population());
+    }
+
+    // The population rises and falls before returning to the start.
+    assert_eq!(populations.iter().max(), Some(&40));
+    assert_eq!(populations.iter().min(), Some(&12));
+    assert_eq!(universe.get_cells(), &cells);
+    assert_eq!(universe.detect_period(20), Some(15));
+}
+
+#[wasm_bindgen_test]
+pub fn test_deploy_lwss() {
+    let mut universe = empty_universe(32, 32);
+    universe.deploy("lwss", 16, 16);
+    assert_eq!(universe.population(), 9);
+
+    // The LWSS moves two cells west every four generations.
+    for _ in 0..4 {
+        universe.tick();
+    }
+
+    let mut expected_universe = empty_universe(32, 32);
+    expected_universe.deploy("LWSS", 16, 14);
+    assert_eq!(universe.get_cells(), expected_universe.get_cells());
+}
+
+#[wasm_bindgen_test]
+pub fn test_deploy_mwss_and_hwss() {
+    for (pattern, population) in [("mwss", 11), ("hwss", 13)] {
+        let mut universe = empty_universe(32, 32);
+        universe.deploy(pattern, 16, 16);
+        assert_eq!(universe.population(), population);
+
+        // Both move one cell west every two generations.
+        for _ in 0..8 {
+            universe.tick();
+        }
+
+        let mut expected_universe = empty_universe(32, 32);
+        expected_universe.deploy(pattern, 16, 12);
+        assert_eq!(universe.get_cells(), expected_universe.get_cells());
+    }
+}
+
+#[wasm_bindgen_test]
+pub fn test_deploy_gosper_glider_gun() {
+    let mut universe = empty_universe(64, 64);
+    universe.deploy("gosper", 5, 5);
+    assert_eq!(universe.population(), 36);
+
+    // The gun is anchored at the top left corner of its bounding box.
+    assert_eq!(universe.population_in_rect(5, 5, 13, 40), 36);
+
+    // Each glider fired adds five cells to the population.
+    for _ in 0..60 {
+        universe.tick();
+    }
+    assert_eq!(universe.population(), 36 + 2 * 5);
+
+    let mut other_universe = empty_universe(64, 64);
+    other_universe.deploy("GliderGun", 5, 5);
+    assert_eq!(other_universe.population(), 36);
+}
+
+#[wasm_bindgen_test]
+pub fn test_deploy_acorn() {
+    let mut universe = empty_universe(64, 64);
+    universe.deploy("acorn", 32, 32);
+    assert_eq!(universe.population(), 7);
+
+    for _ in 0..500 {
+        universe.tick();
+    }
+    assert!(universe.population() > 0);
+}
+
+#[wasm_bindgen_test]
+pub fn test_deploy_still_lifes() {
+    for (pattern, population) in [("block", 4), ("beehive", 6), ("loaf", 7)] {
+        let mut universe = empty_universe(8, 8);
+        universe.deploy(pattern, 4, 4);
+        assert_eq!(universe.population(), population);
+        assert!(universe.is_stable());
+    }
+
+    let mut universe = empty_universe(8, 8);
+    universe.deploy("block", 4, 4);
+    let cells = universe.get_cells().clone();
+    universe.tick();
+    assert_eq!(universe.get_cells(), &cells);
+}
+
+#[wasm_bindgen_test]
+pub fn test_deploy_oriented() {
+    // Unrotated, the glider heads south-east.
+    let mut universe = empty_universe(32, 32);
+    universe.deploy_oriented("glider", 16, 16, 0);
+    let mut expected_universe = empty_universe(32, 32);
+    expected_universe.deploy("glider", 16, 16);
+    assert_eq!(universe.get_cells(), expected_universe.get_cells());
+
+    // Each quarter turn clockwise turns its heading clockwise too.
+    for (rotation, (delta_row, delta_col)) in [(1, (1, -1)), (2, (-1, -1)), (3, (-1, 1))] {
+        let mut universe = empty_universe(32, 32);
+        universe.deploy_oriented("glider", 16, 16, rotation);
+        for _ in 0..4 {
+            universe.tick();
+        }
+
+        let mut expected_universe = empty_universe(32, 32);
+        expected_universe.deploy_oriented(
+            "glider",
+            (16 + delta_row) as u32,
+            (16 + delta_col) as u32,
+            rotation,
+        );
+        assert_eq!(universe.get_cells(), expected_universe.get_cells());
+    }
+}
+
+#[wasm_bindgen_test]
+pub fn test_deploy_transformed() {
+    let mut universe = empty_universe(16, 16);
+    universe.deploy("glider", 8, 8);
+
+    // Mirrored left to right around the anchor column.
+    let mut flipped_universe = empty_universe(16, 16);
+    flipped_universe.deploy_transformed("glider", 8, 8, 0, Flip::Horizontal);
+    assert_eq!(flipped_universe.population(), 5);
+    for row in 0..16 {
+        for col in 1..16 {
+            assert_eq!(
+                flipped_universe.get_cells()[row * 16 + col],
+                universe.get_cells()[row * 16 + (16 - col)]
+            );
+        }
+    }
+
+    // The mirrored glider heads south-west instead of south-east.
+    for _ in 0..4 {
+        flipped_universe.tick();
+    }
+    let mut expected_universe = empty_universe(16, 16);
+    expected_universe.deploy_transformed("glider", 9, 7, 0, Flip::Horizontal);
+    assert_eq!(flipped_universe.get_cells(), expected_universe.get_cells());
+
+    // Flipping and rotating compose: a vertical flip is a horizontal flip
+    // followed by a half turn.
+    let mut vertical_universe = empty_universe(16, 16);
+    vertical_universe.deploy_transformed("glider", 8, 8, 0, Flip::Vertical);
+    let mut composed_universe = empty_universe(16, 16);
+    composed_universe.deploy_transformed("glider", 8, 8, 2, Flip::Horizontal);
+    assert_eq!(vertical_universe.get_cells(), composed_universe.get_cells());
+}
+
+#[wasm_bindgen_test]
+pub fn test_deploy_result() {
+    let mut universe = empty_universe(8, 8);
+    assert!(!universe.deploy("nonsense", 0, 0));
+    assert_eq!(universe.population(), 0);
+    assert!(!universe.undo());
+
+    assert!(universe.deploy("Glider", 4, 4));
+    assert_eq!(universe.population(), 5);
+}
+
+#[wasm_bindgen_test]
+pub fn test_available_patterns() {
+    let patterns = available_patterns();
+    assert!(patterns.contains(&"GLIDER".to_string()));
+    assert!(patterns.contains(&"PULSAR".to_string()));
+
+    // Every listed pattern can be deployed by its name.
+    for pattern in patterns.iter() {
+        let mut universe = empty_universe(64, 64);
+        assert!(universe.deploy(pattern, 32, 32));
+    }
+}
+
+#[wasm_bindgen_test]
+pub fn test_register_pattern() {
+    let mut universe = empty_universe(8, 8);
+    assert!(!universe.deploy("line", 4, 3));
+    assert!(universe
+        .register_pattern("line", "x = 3, y = 1\n3o!")
+        .is_ok());
+
+    // Custom patterns are anchored at their top left corner.
+    assert!(universe.deploy("LINE", 4, 3));
+    let mut expected_universe = empty_universe(8, 8);
+    expected_universe.deploy("blinker", 4, 4);
+    assert_eq!(universe.get_cells(), expected_universe.get_cells());
+
+    // Custom patterns take precedence over the built-in ones.
+    let mut universe = empty_universe(8, 8);
+    universe.register_pattern("glider", "o!").unwrap();
+    universe.deploy("glider", 4, 4);
+    assert_eq!(universe.population(), 1);
+
+    assert!(universe.register_pattern("broken", "3q!").is_err());
+    assert!(!universe.deploy("broken", 4, 4));
+}
+
+#[wasm_bindgen_test]
+pub fn test_tick_many() {
+    let mut universe = empty_universe(16, 16);
+    universe.deploy("glider", 8, 8);
+    let mut expected_universe = empty_universe(16, 16);
+    expected_universe.deploy("glider", 8, 8);
+
+    universe.tick_many(3);
+    for _ in 0..3 {
+        expected_universe.tick();
+    }
+    assert_eq!(universe.get_cells(), expected_universe.get_cells());
+    assert_eq!(universe.generation(), 3);
+
+    // The whole batch is undone at once.
+    universe.undo();
+    let mut deployed_universe = empty_universe(16, 16);
+    deployed_universe.deploy("glider", 8, 8);
+    assert_eq!(universe.get_cells(), deployed_universe.get_cells());
+}
+
+#[wasm_bindgen_test]
+pub fn test_cell_ages() {
+    let mut universe = empty_universe(16, 16);
+    assert!(universe.get_ages().is_empty());
+
+    universe.set_track_ages(true);
+    universe.deploy("block", 4, 4);
+    universe.set_cells(&[(10, 10)]);
+
+    for generation in 1..=3 {
+        universe.tick();
+        assert_eq!(universe.get_ages()[4 * 16 + 4], generation);
+        assert_eq!(universe.get_ages()[5 * 16 + 5], generation);
+    }
+
+    // The lone cell died straight away.
+    assert_eq!(universe.get_ages()[10 * 16 + 10], 0);
+
+    // Toggling a cell off and on again starts its age over.
+    universe.toggle_cell(4, 4);
+    universe.toggle_cell(4, 4);
+    assert_eq!(universe.get_ages()[4 * 16 + 4], 0);
+    assert_eq!(universe.get_ages()[5 * 16 + 5], 3);
+
+    universe.set_track_ages(false);
+    universe.tick();
+    assert!(universe.get_ages().is_empty());
+}
+
+#[wasm_bindgen_test]
+pub fn test_get_cell() {
+    let mut universe = empty_universe(16, 16);
+    universe.deploy("block", 5, 5);
+
+    for (row, col) in [(5, 5), (5, 6), (6, 5), (6, 6)] {
+        assert!(universe.get_cell(row, col));
+    }
+    for (row, col) in [(4, 5), (5, 4), (7, 6), (6, 7), (4, 4), (7, 7)] {
+        assert!(!universe.get_cell(row, col));
+    }
+
+    // Coordinates outside the universe are dead rather than wrapping.
+    assert!(!universe.get_cell(5, 16 + 5));
+    assert!(!universe.get_cell(u32::MAX, u32::MAX));
+}
+
+#[wasm_bindgen_test]
+pub fn test_set_cell() {
+    let mut universe = empty_universe(8, 8);
+
+    // Unlike toggling, setting a cell repeatedly leaves it alive.
+    universe.set_cell(3, 4, true);
+    universe.set_cell(3, 4, true);
+    assert!(universe.get_cell(3, 4));
+    universe.toggle_cell(3, 4);
+    universe.toggle_cell(3, 4);
+    universe.toggle_cell(3, 4);
+    assert!(!universe.get_cell(3, 4));
+
+    universe.set_cell(3, 4, false);
+    assert!(!universe.get_cell(3, 4));
+
+    universe.set_cell(8, 8, true);
+    assert_eq!(universe.population(), 0);
+}
+
+#[wasm_bindgen_test]
+pub fn test_fill_rect() {
+    let mut universe = empty_universe(8, 8);
+    universe.fill_rect(4, 4, 2, 2, true);
+    assert_eq!(universe.population(), 9);
+    assert_eq!(universe.population_in_rect(2, 2, 4, 4), 9);
+
+    universe.fill_rect(3, 3, 3, 3, false);
+    assert_eq!(universe.population(), 8);
+    assert!(!universe.get_cell(3, 3));
+
+    // Corners are clamped to the universe.
+    universe.fill_rect(6, 6, 100, 100, true);
+    assert_eq!(universe.population_in_rect(6, 6, 7, 7), 4);
+}
+
+#[wasm_bindgen_test]
+pub fn test_randomize() {
+    let mut universe = empty_universe(16, 16);
+    universe.randomize(Some(1.0));
+    assert_eq!(universe.population(), 16 * 16);
+
+    universe.randomize(Some(0.0));
+    assert_eq!(universe.population(), 0);
+}
+
+#[cfg(feature = "serde")]
+#[wasm_bindgen_test]
+pub fn test_serde_round_trip() {
+    let mut universe = empty_universe(32, 24);
+    universe.set_rule("B36/S23");
+    universe.deploy("pulsar", 12, 16);
+    universe.tick();
+
+    let serialized = serde_json::to_string(&universe).unwrap();
+    let deserialized: Universe = serde_json::from_str(&serialized).unwrap();
+
+    assert_eq!(deserialized.width(), 32);
+    assert_eq!(deserialized.height(), 24);
+    assert_eq!(deserialized.generation(), 1);
+    assert_eq!(deserialized.get_cells(), universe.get_cells());
+    assert_eq!(deserialized.to_rle(), universe.to_rle());
+
+    // Live cells outside the universe are rejected.
+    let json = r#"{"width":2,"height":2,"generation":0,"rule":"B3/S23","live":[4]}"#;
+    assert!(serde_json::from_str::<Universe>(json).is_err());
+}
+
+#[cfg(feature = "serde")]
+#[wasm_bindgen_test]
+pub fn test_to_json() {
+    let mut universe = empty_universe(32, 24);
+    universe.deploy("glider", 12, 16);
+
+    let json: serde_json::Value = serde_json::from_str(&universe.to_json()).unwrap();
+    assert_eq!(json["width"], 32);
+    assert_eq!(json["height"], 24);
+    assert_eq!(json["generation"], 0);
+    assert_eq!(json["rule"], "B3/S23");
+    assert_eq!(json["live"].as_array().unwrap().len(), 5);
+    assert_eq!(json["live"][0], serde_json::json!([11, 17]));
+}
+
+#[cfg(feature = "serde")]
+#[wasm_bindgen_test]
+pub fn test_from_json() {
+    let mut universe = empty_universe(32, 24);
+    universe.deploy("pulsar", 12, 16);
+    universe.tick();
+
+    let loaded_universe = Universe::from_json(&universe.to_json()).unwrap();
+    assert_eq!(loaded_universe.get_cells(), universe.get_cells());
+    assert_eq!(loaded_universe.generation(), 1);
+    assert_eq!(loaded_universe.to_json(), universe.to_json());
+
+    assert!(Universe::from_json("{").is_err());
+    assert!(Universe::from_json(
+        r#"{"width":2,"height":2,"generation":0,"rule":"B3/S23","live":[[0,2]]}"#
+    )
+    .is_err());
+    assert!(Universe::from_json(
+        r#"{"width":2,"height":2,"generation":0,"rule":"B3/S9","live":[]}"#
+    )
+    .is_err());
+}
+
+#[wasm_bindgen_test]
+pub fn test_changed_cells() {
+    let mut block_universe = empty_universe(8, 8);
+    block_universe.deploy("block", 4, 4);
+    block_universe.tick();
+    assert_eq!(block_universe.changed_count(), 0);
+
+    let mut universe = empty_universe(8, 8);
+    universe.deploy("blinker", 4, 4);
+    let before = universe.get_cells().clone();
+    universe.tick();
+    let after = universe.get_cells().clone();
+
+    let expected = (0..before.len())
+        .filter(|&idx| before[idx] != after[idx])
+        .map(|idx| idx as u32)
+        .collect::<Vec<_>>();
+    assert_eq!(expected, [3 * 8 + 4, 4 * 8 + 3, 4 * 8 + 5, 5 * 8 + 4]);
+    assert_eq!(universe.get_changed_cells(), expected.as_slice());
+    assert_eq!(universe.changed_count(), 4);
+}
+
+#[wasm_bindgen_test]
+pub fn test_tick_reuses_buffers() {
+    let mut universe = empty_universe(256, 256);
+    universe.deploy("pulsar", 128, 128);
+    let mut expected_universe = empty_universe(256, 256);
+    expected_universe.deploy("pulsar", 128, 128);
+    expected_universe.tick();
+
+    // The pulsar has a period of 3, and 1000 is one more than a multiple.
+    universe.tick_many(1000);
+    assert_eq!(universe.get_cells(), expected_universe.get_cells());
+    assert_eq!(universe.detect_period(3), Some(3));
+
+    // The pointer given to JS reads the current generation.
+    let cells = unsafe {
+        std::slice::from_raw_parts(
+            universe.cells() as *const u8,
+            universe.get_cells().as_slice().len() * 4,
+        )
+    };
+    let expected_cells = unsafe {
+        std::slice::from_raw_parts(
+            expected_universe.get_cells().as_slice().as_ptr() as *const u8,
+            expected_universe.get_cells().as_slice().len() * 4,
+        )
+    };
+    assert_eq!(cells, expected_cells);
+}
+
+#[cfg(test)]
+fn naive_tick(universe: &Universe) -> Vec<bool> {
+    let (width, height) = (universe.width(), universe.height());
+    let mut next = Vec::new();
+    for row in 0..height {
+        for col in 0..width {
+            let mut live_neighbors = 0;
+            for delta_row in [height - 1, 0, 1] {
+                for delta_col in [width - 1, 0, 1] {
+                    if (delta_row, delta_col) != (0, 0) {
+                        live_neighbors += universe
+                            .get_cell((row + delta_row) % height, (col + delta_col) % width)
+                            as u8;
+                    }
+                }
+            }
+            let alive = universe.get_cell(row, col);
+            next.push(live_neighbors == 3 || (alive && live_neighbors == 2));
+        }
+    }
+    next
+}
+
+#[wasm_bindgen_test]
+pub fn test_tick_only_recomputes_dirty_cells() {
+    let mut universe = empty_universe(32, 32);
+    universe.deploy("blinker", 16, 16);
+
+    for generation in 0..20 {
+        // Editing the cells must not leave stale cells behind.
+        if generation == 10 {
+            universe.deploy("glider", 4, 4);
+        }
+
+        let expected = naive_tick(&universe);
+        universe.tick();
+        let cells = (0..32 * 32)
+            .map(|idx| universe.get_cell(idx / 32, idx % 32))
+            .collect::<Vec<_>>();
+        assert_eq!(cells, expected);
+    }
+}
+
+#[wasm_bindgen_test]
+pub fn test_glider_trajectory() {
+    let mut universe = empty_universe(16, 16);
+    universe.deploy("glider", 2, 2);
+
+    for _ in 0..40 {
+        let expected = naive_tick(&universe);
+        universe.tick();
+        let cells = (0..16 * 16)
+            .map(|idx| universe.get_cell(idx / 16, idx % 16))
+            .collect::<Vec<_>>();
+        assert_eq!(cells, expected);
+    }
+
+    // A glider moves one cell diagonally every 4 generations, wrapping
+    // around the edges.
+    let mut expected_universe = empty_universe(16, 16);
+    expected_universe.deploy("glider", 12, 12);
+    assert_eq!(universe.get_cells(), expected_universe.get_cells());
+}
+
+#[wasm_bindgen_test]
+pub fn test_single_row_and_column() {
+    for (width, height) in [(5, 1), (1, 5)] {
+        let cell = |universe: &Universe, i: u32| match width {
+            1 => universe.get_cell(i, 0),
+            _ => universe.get_cell(0, i),
+        };
+        let set_cell = |universe: &mut Universe, i: u32| match width {
+            1 => universe.set_cell(i, 0, true),
+            _ => universe.set_cell(0, i, true),
+        };
+
+        // A lone cell has no neighbors, rather than wrapping onto itself.
+        let mut universe = empty_universe(width, height);
+        universe.set_rule("B/S0");
+        set_cell(&mut universe, 2);
+        universe.tick();
+        assert!(cell(&universe, 2));
+
+        // The cells at either end neighbor each other through the wrap.
+        let mut universe = empty_universe(width, height);
+        universe.set_rule("B1/S1");
+        set_cell(&mut universe, 0);
+        set_cell(&mut universe, 4);
+        universe.tick();
+        let cells = (0..5).map(|i| cell(&universe, i)).collect::<Vec<_>>();
+        assert_eq!(cells, [true, true, false, true, true]);
+    }
+}
+
+#[wasm_bindgen_test]
+pub fn test_set_zero_dimensions() {
+    let mut universe = empty_universe(8, 8);
+    universe.set_width(0);
+    universe.set_height(0);
+    assert_eq!(universe.width(), 1);
+    assert_eq!(universe.height(), 1);
+
+    universe.toggle_cell(0, 0);
+    universe.tick();
+    assert!(universe.is_extinct());
+}
+
+#[wasm_bindgen_test]
+pub fn test_display() {
+    let mut universe = empty_universe(6, 4);
+    universe.deploy("block", 1, 2);
+    assert_eq!(universe.to_string(), "◻◻◻◻◻◻\n◻◻◼◼◻◻\n◻◻◼◼◻◻\n◻◻◻◻◻◻\n");
+}
+
+#[wasm_bindgen_test]
+pub fn test_render() {
+    let mut universe = empty_universe(5, 3);
+    universe.set_cell(1, 3, true);
+
+    let text = universe.render('#', '.');
+    assert_eq!(text.matches('#').count(), 1);
+    assert_eq!(text, ".....\n...#.\n.....\n");
+    assert!(text.lines().all(|line| line.chars().count() == 5));
+}
+
+#[wasm_bindgen_test]
+pub fn test_to_bool_grid() {
+    let mut universe = empty_universe(5, 4);
+    universe.deploy("glider", 1, 2);
+
+    let (o, x) = (false, true);
+    assert_eq!(
+        universe.to_bool_grid(),
+        [
+            [o, o, o, x, o],
+            [o, x, o, x, o],
+            [o, o, x, x, o],
+            [o, o, o, o, o],
+        ]
+    );
+}
+
+#[wasm_bindgen_test]
+pub fn test_from_bool_grid() {
+    let (o, x) = (false, true);
+    let horizontal = vec![vec![o, o, o], vec![x, x, x], vec![o, o, o]];
+    let vertical = vec![vec![o, x, o], vec![o, x, o], vec![o, x, o]];
+
+    let mut universe = Universe::from_bool_grid(&horizontal).unwrap();
+    universe.set_boundary(Boundary::Dead);
+    assert_eq!((universe.width(), universe.height()), (3, 3));
+    assert_eq!(universe.to_bool_grid(), horizontal);
+    universe.tick();
+    assert_eq!(universe.to_bool_grid(), vertical);
+    universe.tick();
+    assert_eq!(universe.to_bool_grid(), horizontal);
+
+    assert!(Universe::from_bool_grid(&[vec![o, x], vec![x]]).is_err());
+}
+
+#[wasm_bindgen_test]
+pub fn test_live_bounds() {
+    let mut universe = empty_universe(16, 16);
+    assert_eq!(universe.live_bounds(), None);
+
+    universe.deploy("glider", 5, 7);
+    assert_eq!(universe.live_bounds(), Some((4, 6, 6, 8)));
+}
+
+#[wasm_bindgen_test]
+pub fn test_center() {
+    let mut universe = empty_universe(16, 20);
+    universe.center();
+    assert!(universe.is_extinct());
+
+    universe.deploy("glider", 1, 1);
+    universe.center();
+    let (min_row, min_col, max_row, max_col) = universe.live_bounds().unwrap();
+    assert_eq!((max_row - min_row, max_col - min_col), (2, 2));
+    assert!((min_row + max_row).abs_diff(20 - 1) <= 2);
+    assert!((min_col + max_col).abs_diff(16 - 1) <= 2);
+    assert_eq!(universe.population(), 5);
+}
+
+#[wasm_bindgen_test]
+pub fn test_randomize_symmetric() {
+    let (width, height) = (15, 12);
+    let mut universe = empty_universe(width, height);
+
+    universe.randomize_symmetric(Some(0.5), Axis::Vertical);
+    assert!(!universe.is_extinct());
+    for row in 0..height {
+        for col in 0..width {
+            assert_eq!(
+                universe.get_cell(row, col),
+                universe.get_cell(row, width - 1 - col)
+            );
+        }
+    }
+
+    universe.randomize_symmetric(Some(0.5), Axis::Both);
+    for row in 0..height {
+        for col in 0..width {
+            let cell = universe.get_cell(row, col);
+            assert_eq!(cell, universe.get_cell(height - 1 - row, col));
+            assert_eq!(cell, universe.get_cell(row, width - 1 - col));
+        }
+    }
+}
+
+#[wasm_bindgen_test]
+pub fn test_state_hash() {
+    let mut universe = empty_universe(8, 8);
+    universe.deploy("blinker", 4, 4);
+    let hashes = (0..5)
+        .map(|_| {
+            let hash = universe.state_hash();
+            universe.tick();
+            hash
+        })
+        .collect::<Vec<_>>();
+    assert_ne!(hashes[0], hashes[1]);
+    assert_eq!(hashes[0], hashes[2]);
+    assert_eq!(hashes[1], hashes[3]);
+    assert_eq!(hashes[0], hashes[4]);
+
+    let mut block_universe = empty_universe(8, 8);
+    block_universe.deploy("block", 4, 4);
+    let hash = block_universe.state_hash();
+    block_universe.tick_many(3);
+    assert_eq!(block_universe.state_hash(), hash);
+
+    // The same cells in different dimensions are a different state.
+    let mut wider_universe = empty_universe(9, 8);
+    wider_universe.set_cells(&[(4, 4), (4, 5), (5, 4), (5, 5)]);
+    assert_ne!(wider_universe.state_hash(), hash);
+}
+
+#[wasm_bindgen_test]
+pub fn test_universe_eq() {
+    let mut universe = empty_universe(8, 8);
+    universe.deploy("blinker", 3, 3);
+    let mut other_universe = empty_universe(8, 8);
+    other_universe.set_cells(&[(3, 2), (3, 3), (3, 4)]);
+    assert_eq!(universe, other_universe);
+
+    // The generation doesn't matter, only the cells.
+    universe.tick_many(2);
+    assert_eq!(universe, other_universe);
+
+    other_universe.set_cell(0, 0, true);
+    assert_ne!(universe, other_universe);
+    assert_ne!(empty_universe(8, 4), empty_universe(4, 8));
+}
+
+#[wasm_bindgen_test]
+pub fn test_step_back() {
+    let mut universe = empty_universe(16, 16);
+    universe.deploy("glider", 4, 4);
+    let original_universe = Universe::from_bool_grid(&universe.to_bool_grid()).unwrap();
+
+    for _ in 0..3 {
+        universe.tick();
+    }
+    assert_ne!(universe, original_universe);
+
+    for generation in (0..3).rev() {
+        assert!(universe.step_back());
+        assert_eq!(universe.generation(), generation);
+    }
+    assert_eq!(universe, original_universe);
+
+    // Deploying the glider isn't part of the simulation's timeline.
+    assert!(!universe.step_back());
+    assert_eq!(universe, original_universe);
+
+    // Neither are edits made since the last tick.
+    universe.tick();
+    universe.toggle_cell(0, 0);
+    assert!(!universe.step_back());
+}
+
+#[wasm_bindgen_test]
+pub fn test_toggle_rect() {
+    let mut universe = empty_universe(8, 8);
+    universe.deploy("glider", 4, 4);
+    let original_cells = universe.get_cells().clone();
+
+    // The glider has two live and two dead cells in this block.
+    universe.toggle_rect(4, 5, 3, 4);
+    assert_eq!(universe.population(), 5);
+    assert!(universe.get_cell(3, 4));
+    assert!(!universe.get_cell(3, 5));
+
+    universe.toggle_rect(3, 4, 4, 5);
+    assert_eq!(universe.get_cells(), &original_cells);
+
+    // The corners are clamped to the universe.
+    universe.toggle_rect(6, 6, 100, 100);
+    assert_eq!(universe.population(), 5 + 4);
+}
+
+#[wasm_bindgen_test]
+pub fn test_pulsar_matches_naive_tick() {
+    // Small enough that every generation is computed in full.
+    let mut universe = empty_universe(17, 17);
+    universe.deploy("pulsar", 8, 8);
+
+    for _ in 0..100 {
+        let expected = naive_tick(&universe);
+        universe.tick();
+        let cells = (0..17 * 17)
+            .map(|idx| universe.get_cell(idx / 17, idx % 17))
+            .collect::<Vec<_>>();
+        assert_eq!(cells, expected);
+    }
+    assert_eq!(universe.detect_period(3), Some(3));
+}
+
+#[wasm_bindgen_test]
+pub fn test_pulsar_simd_matches_scalar() {
+    // Wide enough for each row to span several words of 64 cells, with
+    // pulsars straddling a word boundary and each side of the wrapping edge.
+    let mut universe = empty_universe(300, 17);
+    for col in [6, 64, 150, 293] {
+        universe.deploy("pulsar", 8, col);
+    }
+
+    for _ in 0..100 {
+        let expected = universe.next_cells_scalar();
+        #[cfg(feature = "simd")]
+        assert_eq!(universe.next_cells_simd(), expected);
+        universe.tick();
+        assert_eq!(universe.get_cells(), &expected);
+    }
+}
+
+#[wasm_bindgen_test]
+pub fn test_tick_until_stable() {
+    let mut universe = empty_universe(16, 16);
+    universe.deploy("block", 4, 4);
+    assert_eq!(universe.tick_until_stable(100, None), 1);
+    assert_eq!(universe.generation(), 1);
+
+    // A blinker only settles into a cycle of two generations.
+    let mut universe = empty_universe(16, 16);
+    universe.deploy("blinker", 4, 4);
+    assert_eq!(universe.tick_until_stable(100, None), 100);
+    assert_eq!(universe.tick_until_stable(100, Some(2)), 2);
+
+    // A glider never returns to an earlier generation in so few ticks.
+    let mut universe = empty_universe(16, 16);
+    universe.deploy("glider", 4, 4);
+    assert_eq!(universe.tick_until_stable(20, Some(4)), 20);
+    assert_eq!(universe.tick_until_stable(0, None), 0);
+}
+
+#[wasm_bindgen_test]
+pub fn test_run() {
+    let mut universe = empty_universe(16, 16);
+    universe.deploy("blinker", 4, 4);
+
+    let calls = js_sys::Array::new();
+    let callback = js_sys::Function::new_with_args(
+        "generation, population",
+        "this.push([Number(generation), population])",
+    )
+    .bind0(&calls);
+    universe.run(3, &callback).unwrap();
+
+    let calls = calls
+        .iter()
+        .map(|call| String::from(js_sys::Array::from(&call).join(",")))
+        .collect::<Vec<_>>();
+    assert_eq!(calls, ["1,3", "2,3", "3,3"]);
+
+    let throwing = js_sys::Function::new_no_args("throw new Error('stop')");
+    assert!(universe.run(3, &throwing).is_err());
+    assert_eq!(universe.generation(), 4);
+}
+
+#[cfg(feature = "png")]
+#[wasm_bindgen_test]
+pub fn test_to_png() {
+    let mut universe = empty_universe(6, 4);
+    universe.set_cell(1, 2, true);
+    let png = universe.to_png(3).unwrap();
+
+    // A real decoder checks the signature, chunks and their checksums.
+    let mut reader = png::Decoder::new(&png[..]).read_info().unwrap();
+    let mut pixels = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut pixels).unwrap();
+    assert_eq!((info.width, info.height), (6 * 3, 4 * 3));
+    assert_eq!(info.color_type, png::ColorType::Rgba);
+    assert_eq!(info.bit_depth, png::BitDepth::Eight);
+
+    let pixel = |row: u32, col: u32| {
+        let start = ((row * info.width + col) * 4) as usize;
+        &pixels[start..start + 4]
+    };
+    for row in 0..info.height {
+        for col in 0..info.width {
+            let expected = match (row / 3, col / 3) == (1, 2) {
+                true => [0x00, 0x00, 0x00, 0xFF],
+                false => [0xFF, 0xFF, 0xFF, 0xFF],
+            };
+            assert_eq!(pixel(row, col), expected);
+        }
+    }
+
+    // Images with more bytes than fit in a u32 are rejected.
+    assert!(empty_universe(256, 256).to_png(300).is_err());
+}
+
+#[wasm_bindgen_test]
+pub fn test_live_cells() {
+    let mut universe = empty_universe(8, 8);
+    assert_eq!(universe.live_cells().next(), None);
+
+    universe.deploy("block", 2, 5);
+    assert_eq!(
+        universe.live_cells().collect::<Vec<_>>(),
+        [(2, 5), (2, 6), (3, 5), (3, 6)]
+    );
+}
+
+#[wasm_bindgen_test]
+pub fn test_shift() {
+    let mut universe = empty_universe(8, 8);
+    universe.deploy("block", 2, 3);
+    universe.shift(1, 1);
+    assert_eq!(
+        universe.live_cells().collect::<Vec<_>>(),
+        [(3, 4), (3, 5), (4, 4), (4, 5)]
+    );
+
+    // Cells wrap around the edges.
+    universe.shift(-4, 3);
+    assert_eq!(
+        universe.live_cells().collect::<Vec<_>>(),
+        [(0, 0), (0, 7), (7, 0), (7, 7)]
+    );
+    assert!(universe.undo());
+    assert_eq!(universe.population(), 4);
+
+    universe.set_boundary(Boundary::Dead);
+    universe.shift(0, 3);
+    assert_eq!(universe.live_cells().collect::<Vec<_>>(), [(3, 7), (4, 7)]);
+}
+
+#[wasm_bindgen_test]
+pub fn test_density() {
+    let mut universe = empty_universe(200, 200);
+    assert_eq!(universe.density(), 0.0);
+
+    universe.randomize(Some(0.5));
+    assert!((universe.density() - 0.5).abs() < 0.02);
+
+    universe.fill_rect(0, 0, 199, 99, false);
+    universe.fill_rect(0, 100, 199, 199, true);
+    assert_eq!(universe.density(), 0.5);
+}
+
+#[wasm_bindgen_test]
+pub fn test_from_ascii() {
+    let universe = Universe::from_ascii("\n###\n", '#');
+    assert_eq!((universe.width(), universe.height()), (3, 2));
+    assert_eq!(
+        universe.live_cells().collect::<Vec<_>>(),
+        [(1, 0), (1, 1), (1, 2)]
+    );
+
+    let mut universe = Universe::from_ascii(".....\n.....\n.ooo.\n.....\n", 'o');
+    assert_eq!((universe.width(), universe.height()), (5, 4));
+    universe.set_boundary(Boundary::Dead);
+    universe.tick();
+    assert_eq!(universe.render('o', '.'), ".....\n..o..\n..o..\n..o..\n");
+    universe.tick();
+    assert_eq!(universe.render('o', '.'), ".....\n.....\n.ooo.\n.....\n");
+}
+
+#[wasm_bindgen_test]
+pub fn test_rotate_cw() {
+    let mut universe = Universe::from_ascii("#...\n#...\n##..", '#');
+    universe.rotate_cw();
+    assert_eq!((universe.width(), universe.height()), (3, 4));
+    assert_eq!(universe.render('#', '.'), "###\n#..\n...\n...\n");
+
+    // Every cell moves from (r, c) to (c, height - 1 - r).
+    let mut universe = empty_universe(5, 3);
+    universe.set_cells(&[(0, 0), (0, 1), (2, 4)]);
+    universe.rotate_cw();
+    assert_eq!(
+        universe.live_cells().collect::<Vec<_>>(),
+        [(0, 2), (1, 2), (4, 0)]
+    );
+
+    for _ in 0..3 {
+        universe.rotate_cw();
+    }
+    assert_eq!(
+        universe.live_cells().collect::<Vec<_>>(),
+        [(0, 0), (0, 1), (2, 4)]
+    );
+}
+
+#[wasm_bindgen_test]
+pub fn test_flip() {
+    let mut universe = empty_universe(6, 4);
+    universe.set_cell(1, 4, true);
+
+    universe.flip_horizontal();
+    assert_eq!(universe.live_cells().collect::<Vec<_>>(), [(1, 1)]);
+    universe.flip_horizontal();
+    assert_eq!(universe.live_cells().collect::<Vec<_>>(), [(1, 4)]);
+
+    universe.flip_vertical();
+    assert_eq!(universe.live_cells().collect::<Vec<_>>(), [(2, 4)]);
+    universe.flip_vertical();
+    assert_eq!(universe.live_cells().collect::<Vec<_>>(), [(1, 4)]);
+
+    let mut universe = empty_universe(16, 16);
+    universe.deploy("glider", 4, 4);
+    let original_cells = universe.get_cells().clone();
+    universe.flip_horizontal();
+    universe.flip_vertical();
+    assert_ne!(universe.get_cells(), &original_cells);
+    universe.flip_vertical();
+    universe.flip_horizontal();
+    assert_eq!(universe.get_cells(), &original_cells);
+}
+
+#[wasm_bindgen_test]
+pub fn test_copy_region() {
+    let mut universe = empty_universe(8, 8);
+    universe.deploy("block", 0, 0);
+    universe.copy_region(0, 0, 2, 2, 5, 5);
+    assert_eq!(
+        universe.live_cells().collect::<Vec<_>>(),
+        [
+            (0, 0),
+            (0, 1),
+            (1, 0),
+            (1, 1),
+            (5, 5),
+            (5, 6),
+            (6, 5),
+            (6, 6)
+        ]
+    );
+
+    // Dead cells are copied too, and overlapping regions copy what was
+    // there beforehand.
+    universe.copy_region(4, 4, 6, 6, 5, 5);
+    assert_eq!(
+        universe.live_cells().collect::<Vec<_>>(),
+        [
+            (0, 0),
+            (0, 1),
+            (1, 0),
+            (1, 1),
+            (6, 6),
+            (6, 7),
+            (7, 6),
+            (7, 7)
+        ]
+    );
+}
+
+#[wasm_bindgen_test]
+pub fn test_diff() {
+    let mut universe = empty_universe(8, 8);
+    universe.deploy("blinker", 4, 4);
+    let mut next_universe = empty_universe(8, 8);
+    next_universe.deploy("blinker", 4, 4);
+    next_universe.tick();
+
+    assert_eq!(diff(&universe, &universe), Ok(vec![]));
+    assert_eq!(
+        diff(&universe, &next_universe),
+        Ok(vec![(3, 4), (4, 3), (4, 5), (5, 4)])
+    );
+    assert!(diff(&universe, &empty_universe(8, 9)).is_err());
+}
+
+#[wasm_bindgen_test]
+pub fn test_cylinder_boundary() {
+    // A glider heading up and to the right crashes into the top edge,
+    // leaving a block behind.
+    let mut universe = empty_universe(10, 10);
+    universe.set_boundary(Boundary::Cylinder);
+    universe.deploy_transformed("glider", 3, 3, 0, Flip::Vertical);
+    universe.tick_many(20);
+    assert_eq!(
+        universe.live_cells().collect::<Vec<_>>(),
+        [(0, 6), (0, 7), (1, 6), (1, 7)]
+    );
+
+    // A glider heading down and to the right wraps around to the left.
+    let mut universe = empty_universe(10, 20);
+    universe.set_boundary(Boundary::Cylinder);
+    universe.deploy("glider", 5, 7);
+    universe.tick_many(20);
+    let mut expected_universe = empty_universe(10, 20);
+    expected_universe.deploy("glider", 10, 2);
+    assert_eq!(universe, expected_universe);
+}
+
+#[wasm_bindgen_test]
+pub fn test_sparse_round_trip() {
+    let mut universe = empty_universe(64, 32);
+    universe.set_cells(&[(0, 0), (10, 20), (31, 63)]);
+
+    let data = universe.to_sparse();
+    assert_eq!(data, [64, 32, 0, 10 * 64 + 20, 31 * 64 + 63]);
+    let decoded_universe = Universe::from_sparse(&data).unwrap();
+    assert_eq!(decoded_universe, universe);
+
+    assert!(Universe::from_sparse(&[64]).is_err());
+    assert!(Universe::from_sparse(&[0, 32]).is_err());
+    assert!(Universe::from_sparse(&[64, 32, 64 * 32]).is_err());
+}
+
+#[wasm_bindgen_test]
+pub fn test_reset() {
+    let mut universe = Universe::new_seeded(7, Some(0.3));
+    universe.tick_many(10);
+    assert_ne!(universe, Universe::new_seeded(7, Some(0.3)));
+    assert!(universe.reset());
+    assert_eq!(universe, Universe::new_seeded(7, Some(0.3)));
+    assert_eq!(universe.generation(), 0);
+
+    // Deployed patterns are only part of the initial state once marked.
+    let mut universe = empty_universe(16, 16);
+    universe.deploy("glider", 4, 4);
+    universe.tick();
+    assert!(universe.reset());
+    assert!(universe.is_extinct());
+
+    universe.deploy("glider", 4, 4);
+    universe.mark_initial();
+    let initial_cells = universe.get_cells().clone();
+    universe.tick_many(10);
+    assert!(universe.reset());
+    assert_eq!(universe.get_cells(), &initial_cells);
+
+    universe.resize(8, 8).unwrap();
+    assert!(!universe.reset());
+}
+
+#[wasm_bindgen_test]
+pub fn test_detect_spaceship() {
+    let mut universe = empty_universe(32, 32);
+    universe.set_boundary(Boundary::Dead);
+    universe.deploy("glider", 8, 8);
+    let cells = universe.get_cells().clone();
+
+    assert_eq!(universe.detect_spaceship(3), None);
+    assert_eq!(universe.detect_spaceship(10), Some((4, 1, 1)));
+    assert_eq!(universe.get_cells(), &cells);
+    assert_eq!(universe.generation(), 0);
+
+    let mut universe = empty_universe(32, 32);
+    universe.set_boundary(Boundary::Dead);
+    universe.deploy("lwss", 16, 16);
+    assert_eq!(universe.detect_spaceship(10), Some((4, 0, -2)));
+
+    // Oscillators don't go anywhere.
+    let mut universe = empty_universe(32, 32);
+    universe.deploy("blinker", 16, 16);
+    assert_eq!(universe.detect_spaceship(10), None);
+    assert_eq!(empty_universe(8, 8).detect_spaceship(10), None);
+}
+
+#[wasm_bindgen_test]
+pub fn test_population_history() {
+    let mut universe = empty_universe(32, 32);
+    universe.deploy("pulsar", 16, 16);
+    assert!(universe.population_history().is_empty());
+
+    for _ in 0..9 {
+        universe.tick();
+    }
+    let history = universe.population_history();
+    assert_eq!(history.len(), 9);
+    // The pulsar has period 3, so its population does too, without being
+    // constant.
+    assert_eq!(history[0..3], history[3..6]);
+    assert_eq!(history[0..3], history[6..9]);
+    assert!(history[0..3].iter().any(|&pop| pop != history[0]));
+    assert_eq!(history[8], universe.population());
+
+    universe.set_population_history_limit(4);
+    assert_eq!(universe.population_history(), history[5..9]);
+    universe.tick();
+    assert_eq!(universe.population_history().len(), 4);
+
+    universe.reset();
+    assert!(universe.population_history().is_empty());
+    universe.tick();
+    universe.clear();
+    assert!(universe.population_history().is_empty());
+}
+
+#[wasm_bindgen_test]
+pub fn test_capacity() {
+    let mut universe = empty_universe(256, 256);
+    assert_eq!(universe.capacity(), 256 * 256);
+
+    universe.resize(16, 16).unwrap();
+    assert_eq!(universe.capacity(), 256);
+    universe.set_width(8);
+    assert_eq!(universe.capacity(), 128);
+    universe.set_height(4);
+    assert_eq!(universe.capacity(), 32);
+    universe.rotate_cw();
+    assert_eq!(universe.capacity(), 32);
+}
+
+#[wasm_bindgen_test]
+pub fn test_size_overflow() {
+    // 70000 * 70000 cells don't fit in a u32.
+    assert!(Universe::with_size(70000, 70000, Some(0.0)).is_err());
+    assert!(Universe::from_sparse(&[70000, 70000]).is_err());
+    assert!(Universe::from_sparse(&[u32::MAX, 2, 0]).is_err());
+
+    // Resizing fails without changing the universe.
+    let mut universe = empty_universe(8, 8);
+    universe.set_cell(1, 2, true);
+    assert!(universe.resize(70000, 70000).is_err());
+    assert_eq!((universe.width(), universe.height()), (8, 8));
+    assert!(universe.get_cell(1, 2));
+
+    #[cfg(feature = "serde")]
+    {
+        let json = r#"{"width":70000,"height":70000,"generation":0,"rule":"B3/S23","live":[]}"#;
+        assert!(Universe::from_json(json).is_err());
+        assert!(serde_json::from_str::<Universe>(json).is_err());
+    }
+}
+
+#[wasm_bindgen_test]
+pub fn test_base64_round_trip() {
+    let mut universe = empty_universe(32, 32);
+    universe.randomize(Some(0.5));
+
+    let encoded = universe.to_base64();
+    assert!(encoded
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+    let decoded = Universe::from_base64(&encoded).unwrap();
+    assert_eq!(decoded, universe);
+
+    // Partial bytes at the end are padded with dead cells.
+    let mut universe = empty_universe(3, 3);
+    universe.toggle_cell(2, 2);
+    assert_eq!(
+        Universe::from_base64(&universe.to_base64()).unwrap(),
+        universe
+    );
+
+    assert!(Universe::from_base64("not base64!").is_err());
+    assert!(Universe::from_base64("AQAAAA").is_err());
+    assert!(Universe::from_base64(&universe.to_base64()[..12]).is_err());
+}
+
+#[wasm_bindgen_test]
+pub fn test_insert_life106() {
+    let glider = "#Life 1.06\n0 -1\n1 0\n-1 1\n0 1\n1 1\n";
+    let mut universe = empty_universe(8, 8);
+    universe.insert_life106(glider).unwrap();
+
+    let mut expected = empty_universe(8, 8);
+    expected.set_cells(&[(3, 4), (4, 5), (5, 3), (5, 4), (5, 5)]);
+    assert_eq!(universe, expected);
+
+    // Cells that fall outside the universe are dropped.
+    let mut universe = empty_universe(8, 8);
+    universe
+        .insert_life106("#Life 1.06\n0 0\n-5 0\n0 4\n3 3\n")
+        .unwrap();
+    assert_eq!(universe.live_cells().collect::<Vec<_>>(), [(4, 4), (7, 7)]);
+
+    assert!(universe.insert_life106("#Life 1.06\n0\n").is_err());
+    assert!(universe.insert_life106("1 2 3").is_err());
+    assert!(universe.insert_life106("a b").is_err());
+    assert_eq!(universe.population(), 2);
+}
+
+#[wasm_bindgen_test]
+pub fn test_to_life106() {
+    assert_eq!(empty_universe(8, 8).to_life106(), "#Life 1.06\n");
+
+    let mut universe = empty_universe(8, 8);
+    universe.set_cells(&[(2, 3), (3, 5)]);
+    assert_eq!(universe.to_life106(), "#Life 1.06\n0 0\n2 1\n");
+
+    let mut universe = empty_universe(32, 32);
+    universe.deploy("pulsar", 16, 16);
+    let text = universe.to_life106();
+    assert_eq!(text.lines().count(), 1 + universe.population() as usize);
+
+    let mut imported = empty_universe(32, 32);
+    imported.insert_life106(&text).unwrap();
+    assert_eq!(imported.to_life106(), text);
+    assert_eq!(imported.to_rle(), universe.to_rle());
+}
+
+#[wasm_bindgen_test]
+pub fn test_rle_rule() {
+    let highlife_glider = "x = 3, y = 3, rule = B36/S23\nbob$2bo$3o!";
+
+    let mut universe = empty_universe(16, 16);
+    universe.insert_rle(highlife_glider, 4, 4).unwrap();
+    assert!(universe.to_rle().contains("rule = B36/S23"));
+
+    // An absent or unknown rule falls back to Conway's Game of Life.
+    universe.insert_rle("bo$o!", 0, 0).unwrap();
+    assert!(universe.to_rle().contains("rule = B3/S23"));
+    universe.set_ruleset("HighLife");
+    universe
+        .insert_rle("x = 2, y = 2, rule = 23/3/Hex\nbo$o!", 0, 0)
+        .unwrap();
+    assert!(universe.to_rle().contains("rule = B3/S23"));
+
+    // Six neighbors give birth under HighLife.
+    let mut universe = empty_universe(16, 16);
+    universe
+        .load_rle("x = 3, y = 3, rule = B36/S23\n3o$o$2o!")
+        .unwrap();
+    assert_eq!(universe.generation(), 0);
+    assert!(universe
+        .to_rle()
+        .starts_with("x = 3, y = 3, rule = B36/S23\n"));
+    assert!(universe.get_cell(7, 6));
+    universe.tick();
+    assert!(universe.get_cell(7, 7));
+}
+
+#[wasm_bindgen_test]
+pub fn test_load_rle() {
+    let mut universe = empty_universe(16, 16);
+    universe.deploy("pulsar", 8, 8);
+    universe.tick();
+
+    universe.load_rle("x = 3, y = 3\nbob$2bo$3o!").unwrap();
+    assert_eq!(universe.generation(), 0);
+    assert_eq!(
+        universe.live_cells().collect::<Vec<_>>(),
+        [(6, 7), (7, 8), (8, 6), (8, 7), (8, 8)]
+    );
+
+    // Loading replaces what `reset` returns to.
+    universe.tick();
+    universe.reset();
+    assert_eq!(universe.population(), 5);
+    assert_eq!(universe.live_bounds(), Some((6, 6, 8, 8)));
+
+    assert!(universe.load_rle("bob$2bo$3o").is_err());
+    assert_eq!(universe.population(), 5);
+}
+
+#[wasm_bindgen_test]
+pub fn test_draw_line() {
+    let mut universe = empty_universe(8, 8);
+    universe.draw_line(0, 0, 5, 5, true);
+    assert_eq!(
+        universe.live_cells().collect::<Vec<_>>(),
+        (0..6).map(|i| (i, i)).collect::<Vec<_>>()
+    );
+
+    // Shallow and steep lines touch one cell per column or row respectively,
+    // in either direction.
+    let mut universe = empty_universe(8, 8);
+    universe.draw_line(3, 7, 1, 0, true);
+    assert_eq!(universe.population(), 8);
+    assert!(universe.get_cell(3, 7) && universe.get_cell(1, 0));
+    let columns = universe
+        .live_cells()
+        .map(|(_, col)| col)
+        .collect::<Vec<_>>();
+    assert!((0..8).all(|col| columns.contains(&col)));
+
+    let mut universe = empty_universe(8, 8);
+    universe.draw_line(0, 2, 7, 4, true);
+    assert_eq!(universe.population(), 8);
+    assert!(universe.get_cell(0, 2) && universe.get_cell(7, 4));
+    let rows = universe
+        .live_cells()
+        .map(|(row, _)| row)
+        .collect::<Vec<_>>();
+    assert_eq!(rows, (0..8).collect::<Vec<_>>());
+
+    // A line of a single cell, and erasing part of a line.
+    universe.draw_line(6, 6, 6, 6, true);
+    assert!(universe.get_cell(6, 6));
+    universe.draw_line(0, 0, 7, 7, false);
+    universe.draw_line(0, 2, 3, 3, false);
+    assert_eq!(universe.population(), 4);
+
+    // Cells past the edges are ignored rather than wrapped.
+    let mut universe = empty_universe(8, 8);
+    universe.draw_line(6, 6, 10, 10, true);
+    assert_eq!(universe.population(), 2);
+}
+
+#[wasm_bindgen_test]
+pub fn test_flood_fill() {
+    // A closed ring of live cells around a 3x3 interior.
+    let mut universe = empty_universe(10, 10);
+    universe.draw_line(2, 2, 2, 6, true);
+    universe.draw_line(6, 2, 6, 6, true);
+    universe.draw_line(2, 2, 6, 2, true);
+    universe.draw_line(2, 6, 6, 6, true);
+    assert_eq!(universe.population(), 16);
+
+    universe.flood_fill(4, 4, true);
+    assert_eq!(universe.population(), 25);
+    assert_eq!(universe.live_bounds(), Some((2, 2, 6, 6)));
+    assert!(!universe.get_cell(0, 0));
+
+    // Filling the outside stops at the edges and at the ring.
+    universe.flood_fill(0, 0, true);
+    assert_eq!(universe.population(), 100);
+    universe.flood_fill(9, 9, false);
+    assert_eq!(universe.population(), 0);
+
+    // Filling with the state the cell already has changes nothing.
+    universe.flood_fill(5, 5, false);
+    universe.flood_fill(10, 10, true);
+    assert_eq!(universe.population(), 0);
+
+    // Cells only touching diagonally are not connected.
+    let mut universe = empty_universe(4, 4);
+    universe.set_cells(&[(0, 0), (1, 1), (2, 2)]);
+    universe.flood_fill(1, 1, false);
+    assert_eq!(universe.population(), 2);
+}
+
+#[wasm_bindgen_test]
+pub fn test_run_until_cycle() {
+    let mut universe = empty_universe(16, 16);
+    universe.deploy("blinker", 8, 8);
+    assert_eq!(universe.run_until_cycle(100, None), 2);
+    assert_eq!(universe.generation(), 2);
+
+    // An empty universe stays the same after a single generation.
+    let mut universe = empty_universe(16, 16);
+    assert_eq!(universe.run_until_cycle(100, None), 1);
+    assert_eq!(universe.generation(), 1);
+
+    // The pulsar's period of 3 is longer than the window.
+    let mut universe = empty_universe(32, 32);
+    universe.deploy("pulsar", 16, 16);
+    assert_eq!(universe.run_until_cycle(20, Some(2)), 0);
+    assert_eq!(universe.generation(), 20);
+    assert_eq!(universe.run_until_cycle(20, Some(3)), 3);
+
+    // A glider on a torus returns after crossing the whole universe.
+    let mut universe = empty_universe(8, 8);
+    universe.deploy("glider", 4, 4);
+    assert_eq!(universe.run_until_cycle(100, None), 32);
+    assert_eq!(universe.generation(), 32);
+}
+
+#[wasm_bindgen_test]
+pub fn test_neighbor_count() {
+    let mut universe = empty_universe(8, 8);
+    universe.set_cells(&[(3, 4), (4, 3), (4, 4), (4, 5), (5, 4)]);
+    assert_eq!(universe.neighbor_count(4, 4), 4);
+    assert_eq!(universe.neighbor_count(3, 3), 3);
+    assert_eq!(universe.neighbor_count(2, 4), 1);
+    assert_eq!(universe.neighbor_count(8, 4), 0);
+
+    // Neighbors across the edges depend on the boundary.
+    let mut universe = empty_universe(8, 8);
+    universe.set_cells(&[(0, 7), (7, 0), (7, 7)]);
+    assert_eq!(universe.neighbor_count(0, 0), 3);
+    universe.set_boundary(Boundary::Dead);
+    assert_eq!(universe.neighbor_count(0, 0), 0);
+    universe.set_boundary(Boundary::Cylinder);
+    assert_eq!(universe.neighbor_count(0, 0), 1);
+}
+
+#[wasm_bindgen_test]
+pub fn test_neighbor_count_radius() {
+    let mut universe = empty_universe(16, 16);
+    universe.fill_rect(4, 4, 8, 8, true);
+    assert_eq!(universe.neighbor_count_radius(6, 6, 0), 0);
+    assert_eq!(universe.neighbor_count_radius(6, 6, 1), 8);
+    assert_eq!(universe.neighbor_count_radius(6, 6, 2), 24);
+    assert_eq!(universe.neighbor_count_radius(6, 6, 3), 24);
+    assert_eq!(universe.neighbor_count_radius(4, 4, 2), 8);
+    assert_eq!(universe.neighbor_count_radius(2, 2, 2), 1);
+    assert_eq!(universe.neighbor_count_radius(16, 2, 2), 0);
+
+    // Radius 1 agrees with the count for the rule on a torus.
+    for (row, col) in [(3, 3), (4, 6), (9, 9)] {
+        assert_eq!(
+            universe.neighbor_count_radius(row, col, 1),
+            universe.neighbor_count(row, col) as u32
+        );
+    }
+
+    // Neighborhoods wrap around, without counting any cell twice.
+    let mut universe = empty_universe(4, 4);
+    universe.fill_rect(0, 0, 3, 3, true);
+    assert_eq!(universe.neighbor_count_radius(0, 0, 1), 8);
+    assert_eq!(universe.neighbor_count_radius(0, 0, 10), 15);
+}
+
+#[wasm_bindgen_test]
+pub fn test_immigration() {
+    let mut universe = empty_universe(8, 8);
+    for (row, col) in [(2, 2), (2, 3), (2, 4)] {
+        universe.set_cell_color(row, col, 1);
+    }
+    universe.set_cell_color(6, 6, 2);
+    universe.tick_immigration();
+    assert_eq!(universe.cell_color(1, 3), 1);
+    assert_eq!(universe.cell_color(3, 3), 1);
+    assert_eq!(universe.cell_color(2, 3), 1);
+    assert_eq!(universe.cell_color(2, 2), 0);
+    assert_eq!(universe.cell_color(6, 6), 0);
+
+    // Births take the majority color of their parents.
+    let mut universe = empty_universe(8, 8);
+    universe.set_cell_color(2, 2, 2);
+    universe.set_cell_color(2, 3, 1);
+    universe.set_cell_color(2, 4, 2);
+    universe.tick_immigration();
+    assert_eq!(universe.cell_color(1, 3), 2);
+    assert_eq!(universe.cell_color(3, 3), 2);
+    assert_eq!(universe.cell_color(2, 3), 1);
+    assert_eq!(universe.generation(), 1);
+
+    // The cells follow B3/S23 whatever the rule.
+    let mut conway = empty_universe(16, 16);
+    conway.deploy("pulsar", 8, 8);
+    let mut immigration = empty_universe(16, 16);
+    immigration.deploy("pulsar", 8, 8);
+    immigration.set_rule("B36/S23");
+    for _ in 0..3 {
+        conway.tick();
+        immigration.tick_immigration();
+        assert_eq!(immigration.get_cells(), conway.get_cells());
+    }
+
+    // Cells without a color set are color 1.
+    assert_eq!(immigration.cell_color(8, 8), 0);
+    assert_eq!(immigration.cell_color(2, 4), 1);
+    assert_eq!(immigration.cell_color(16, 16), 0);
+}
+
+#[wasm_bindgen_test]
+pub fn test_snapshot() {
+    let mut universe = empty_universe(16, 16);
+    universe.deploy("glider", 8, 8);
+    universe.tick();
+    let snapshot: Snapshot = universe.snapshot();
+    let cells = universe.get_cells().clone();
+
+    universe.tick_many(10);
+    assert_ne!(universe.get_cells(), &cells);
+    universe.restore(&snapshot);
+    assert_eq!(universe.get_cells(), &cells);
+    assert_eq!(universe.generation(), 1);
+
+    // Restoring can be undone.
+    assert!(universe.undo());
+    assert_ne!(universe.get_cells(), &cells);
+
+    // Restoring also restores the dimensions.
+    universe.resize(8, 4).unwrap();
+    universe.restore(&snapshot);
+    assert_eq!((universe.width(), universe.height()), (16, 16));
+    assert_eq!(universe.get_cells(), &cells);
+    assert_eq!(universe.snapshot(), snapshot);
+}
+
+#[wasm_bindgen_test]
+pub fn test_cells_bytes() {
+    let block_size = std::mem::size_of::<usize>();
+    for (width, height) in [(8, 8), (7, 3), (33, 5), (64, 64)] {
+        let universe = empty_universe(width, height);
+        let bytes = universe.cells_bytes();
+        let len = ((width * height) as usize).div_ceil(8);
+        assert_eq!(bytes.len(), len.div_ceil(block_size) * block_size);
+    }
+
+    let mut universe = empty_universe(16, 4);
+    universe.set_cells(&[(0, 0), (0, 9), (3, 15)]);
+    let bytes = universe.cells_bytes();
+    assert_eq!(bytes[0], 0b1);
+    assert_eq!(bytes[1], 0b10);
+    assert_eq!(bytes[7], 0b1000_0000);
+    assert_eq!(bytes.iter().map(|byte| byte.count_ones()).sum::<u32>(), 3);
+}
+
+#[wasm_bindgen_test]
+pub fn test_invert() {
+    let mut universe = empty_universe(8, 6);
+    universe.deploy("glider", 3, 3);
+    universe.set_track_ages(true);
+    universe.tick();
+    let cells = universe.get_cells().clone();
+
+    universe.invert();
+    assert_eq!(universe.population(), 48 - 5);
+    assert!(universe
+        .live_cells()
+        .all(|(row, col)| !cells[(row * 8 + col) as usize]));
+    assert!(universe.get_ages().iter().all(|&age| age == 0));
+
+    universe.invert();
+    assert_eq!(universe.get_cells(), &cells);
+}
+
+#[wasm_bindgen_test]
+pub fn test_transpose() {
+    // An L shape in a universe 6 wide and 4 high.
+    let mut universe = empty_universe(6, 4);
+    universe.set_cells(&[(0, 1), (1, 1), (2, 1), (2, 2), (2, 3), (3, 5)]);
+
+    universe.transpose();
+    assert_eq!((universe.width(), universe.height()), (4, 6));
+    assert_eq!(
+        universe.live_cells().collect::<Vec<_>>(),
+        [(1, 0), (1, 1), (1, 2), (2, 2), (3, 2), (5, 3)]
+    );
+
+    universe.transpose();
+    assert_eq!((universe.width(), universe.height()), (6, 4));
+    assert_eq!(
+        universe.live_cells().collect::<Vec<_>>(),
+        [(0, 1), (1, 1), (2, 1), (2, 2), (2, 3), (3, 5)]
+    );
+}
+
+#[wasm_bindgen_test]
+pub fn test_births_and_deaths() {
+    let mut universe = empty_universe(8, 8);
+    universe.deploy("blinker", 4, 4);
+    assert_eq!((universe.last_births(), universe.last_deaths()), (0, 0));
+    for _ in 0..4 {
+        universe.tick();
+        assert_eq!((universe.last_births(), universe.last_deaths()), (2, 2));
+    }
+
+    // A dying pair of cells has deaths and no births.
+    let mut universe = empty_universe(8, 8);
+    universe.set_cells(&[(4, 4), (4, 5)]);
+    universe.tick();
+    assert_eq!((universe.last_births(), universe.last_deaths()), (0, 2));
+    universe.tick();
+    assert_eq!((universe.last_births(), universe.last_deaths()), (0, 0));
+}
+
+#[wasm_bindgen_test]
+pub fn test_history_limit() {
+    let mut universe = empty_universe(16, 16);
+    universe.deploy("glider", 8, 8);
+    universe.set_history_limit(3);
+
+    let mut states = vec![universe.get_cells().clone()];
+    for _ in 0..10 {
+        universe.tick();
+        states.push(universe.get_cells().clone());
+        assert!(universe.history_len() <= 3);
+    }
+    assert_eq!(universe.history_len(), 3);
+
+    // Only the most recent states can be undone.
+    for state in states[7..10].iter().rev() {
+        assert!(universe.undo());
+        assert_eq!(universe.get_cells(), state);
+    }
+    assert!(!universe.undo());
+
+    // Lowering the limit discards the oldest states.
+    universe.set_history_limit(10);
+    for _ in 0..5 {
+        universe.tick();
+    }
+    universe.set_history_limit(2);
+    assert_eq!(universe.history_len(), 2);
+
+    universe.set_history_limit(0);
+    assert_eq!(universe.history_len(), 0);
+    universe.tick();
+    assert_eq!(universe.history_len(), 0);
+    assert!(!universe.undo());
+}
+
+#[wasm_bindgen_test]
+pub fn test_to_rgba() {
+    let mut universe = empty_universe(5, 3);
+    universe.set_cells(&[(1, 2)]);
+
+    let alive = [255, 128, 0, 255];
+    let dead = [16, 16, 16, 0];
+    let rgba = universe.to_rgba(alive, dead);
+    assert_eq!(rgba.len(), 5 * 3 * 4);
+    let idx = (5 + 2) * 4;
+    assert_eq!(rgba[idx..idx + 4], alive);
+    assert_eq!(rgba[0..4], dead);
+    assert!(rgba
+        .chunks(4)
+        .enumerate()
+        .all(|(cell, color)| color == if cell == 7 { alive } else { dead }));
+
+    assert_eq!(universe.rgba_texture(0xff8000ff, 0x10101000), rgba);
+}
+
+#[wasm_bindgen_test]
+pub fn test_acorn_matches_naive_tick() {
+    // Wider than a 64-cell word, and not a multiple of one.
+    let mut universe = empty_universe(100, 72);
+    universe.deploy("acorn", 36, 50);
+
+    for _ in 0..200 {
+        let expected = naive_tick(&universe);
+        // Setting the boundary forgets the last generation, so that every
+        // cell is recomputed rather than just those near the last changes.
+        universe.set_boundary(Boundary::Toroidal);
+        universe.tick();
+        let cells = (0..universe.width() * universe.height())
+            .map(|idx| universe.get_cells()[idx as usize])
+            .collect::<Vec<_>>();
+        assert_eq!(cells, expected);
+    }
+}
+
+#[wasm_bindgen_test]
+pub fn test_tick_matches_tick_with() {
+    // The rule passed to `tick_with` is applied one cell at a time.
+    for boundary in [
+        Boundary::Toroidal,
+        Boundary::Dead,
+        Boundary::Reflective,
+        Boundary::Cylinder,
+    ] {
+        for (width, height) in [(1, 5), (2, 3), (3, 3), (63, 4), (64, 5), (65, 7), (130, 3)] {
+            let mut universe = empty_universe(width, height);
+            universe.set_boundary(boundary);
+            universe.randomize(Some(0.4));
+            let mut reference = Universe::from_sparse(&universe.to_sparse()).unwrap();
+            reference.set_boundary(boundary);
+            reference.set_rule("B36/S23");
+            universe.set_rule("B36/S23");
+
+            for _ in 0..5 {
+                universe.set_rule("B36/S23");
+                universe.tick();
+                reference.tick_with(|alive, live_neighbors| match alive {
+                    true => live_neighbors == 2 || live_neighbors == 3,
+                    false => live_neighbors == 3 || live_neighbors == 6,
+                });
+                assert_eq!(universe.get_cells(), reference.get_cells());
+            }
+        }
+    }
+}
+
+#[wasm_bindgen_test]
+pub fn test_tick_collect() {
+    let mut universe = empty_universe(16, 16);
+    universe.deploy("blinker", 8, 8);
+    assert_eq!(universe.tick_collect(6), [3; 6]);
+    assert_eq!(universe.generation(), 6);
+    assert!(universe.tick_collect(0).is_empty());
+
+    // The populations are those after each step.
+    let mut universe = empty_universe(16, 16);
+    universe.set_cells(&[(4, 4), (4, 5), (5, 4)]);
+    assert_eq!(universe.tick_collect(3), [4, 4, 4]);
+    universe.clear();
+    universe.set_cells(&[(4, 4), (4, 5)]);
+    assert_eq!(universe.tick_collect(3), [0, 0, 0]);
+
+    // All of the generations are undone together.
+    assert!(universe.undo());
+    assert_eq!(universe.population(), 2);
+}
+
+#[wasm_bindgen_test]
+pub fn test_auto_grow_boundary() {
+    let mut universe = empty_universe(16, 16);
+    universe.set_boundary(Boundary::AutoGrow);
+    universe.deploy("glider", 10, 10);
+    let (min_row, min_col, _, _) = universe.live_bounds().unwrap();
+
+    // The glider heads down and right, so the universe grows that way
+    // without moving it.
+    for _ in 0..40 {
+        universe.tick();
+        assert_eq!(universe.population(), 5);
+    }
+    assert!(universe.width() > 16 && universe.height() > 16);
+    assert_eq!(universe.live_bounds().unwrap().0, min_row + 10);
+    assert_eq!(universe.live_bounds().unwrap().1, min_col + 10);
+    assert_eq!(universe.detect_spaceship(4), Some((4, 1, 1)));
+
+    // Growing up or left moves the cells, in the undo history too.
+    let mut universe = empty_universe(8, 8);
+    universe.set_boundary(Boundary::AutoGrow);
+    universe.set_cells(&[(0, 3), (0, 4), (0, 5)]);
+    universe.tick();
+    assert_eq!((universe.width(), universe.height()), (8, 24));
+    assert_eq!(
+        universe.live_cells().collect::<Vec<_>>(),
+        [(15, 4), (16, 4), (17, 4)]
+    );
+    assert!(universe.undo());
+    assert_eq!(
+        universe.live_cells().collect::<Vec<_>>(),
+        [(16, 3), (16, 4), (16, 5)]
+    );
+
+    // The universe grows before other kinds of tick too.
+    let mut universe = empty_universe(8, 8);
+    universe.set_boundary(Boundary::AutoGrow);
+    universe.set_cells(&[(0, 3), (0, 4), (0, 5)]);
+    universe.tick_with(|alive, neighbors| neighbors == 3 || alive && neighbors == 2);
+    assert_eq!((universe.width(), universe.height()), (8, 24));
+    assert_eq!(
+        universe.live_cells().collect::<Vec<_>>(),
+        [(15, 4), (16, 4), (17, 4)]
+    );
+    universe.tick_immigration();
+    assert_eq!((universe.width(), universe.height()), (8, 24));
+    assert_eq!(
+        universe.live_cells().collect::<Vec<_>>(),
+        [(16, 3), (16, 4), (16, 5)]
+    );
+
+    // Growth stops at the limit, where the edges are dead.
+    let mut universe = empty_universe(16, 16);
+    universe.set_boundary(Boundary::AutoGrow);
+    universe.set_auto_grow_limit(20, 24);
+    universe.deploy("glider", 10, 10);
+    universe.tick_many(100);
+    assert_eq!((universe.width(), universe.height()), (20, 24));
+    assert_ne!(universe.population(), 5);
+}
+
+#[wasm_bindgen_test]
+pub fn test_has_pattern() {
+    let mut universe = empty_universe(8, 8);
+    assert!(universe.has_pattern("glider"));
+    assert!(universe.has_pattern("GLIDER"));
+    assert!(!universe.has_pattern("foobar"));
+    assert!(available_patterns()
+        .iter()
+        .all(|name| universe.has_pattern(name)));
+
+    universe.register_pattern("foobar", "3o!").unwrap();
+    assert!(universe.has_pattern("FooBar"));
+    assert_eq!(universe.population(), 0);
+}
+
+#[wasm_bindgen_test]
+pub fn test_pattern_bounds() {
+    let mut universe = empty_universe(8, 8);
+    assert_eq!(universe.pattern_bounds("glider"), Some((-1, -1, 1, 1)));
+    assert_eq!(universe.pattern_bounds("PULSAR"), Some((-6, -6, 6, 6)));
+    assert_eq!(universe.pattern_bounds("foobar"), None);
+    assert_eq!(
+        universe.pattern_bounds_array("glider"),
+        Some(vec![-1, -1, 1, 1])
+    );
+    assert_eq!(universe.pattern_bounds_array("foobar"), None);
+
+    // The bounds match where a deployed pattern lands.
+    universe.deploy("glider", 4, 4);
+    assert_eq!(universe.live_bounds(), Some((3, 3, 5, 5)));
+
+    universe
+        .register_pattern("line", "x = 3, y = 1\n3o!")
+        .unwrap();
+    assert_eq!(universe.pattern_bounds("line"), Some((0, 0, 0, 2)));
+    universe.register_pattern("empty", "!").unwrap();
+    assert_eq!(universe.pattern_bounds("empty"), None);
+}
+
+#[wasm_bindgen_test]
+pub fn test_tick_after_immigration() {
+    // A block and a blinker, which B3/S23 leaves as a block and a blinker.
+    let cells = [(2, 2), (2, 3), (3, 2), (3, 3), (10, 9), (10, 10), (10, 11)];
+    let mut universe = empty_universe(16, 16);
+    universe.set_cells(&cells);
+    universe.set_rule("B2/S");
+    universe.tick_immigration();
+
+    let mut expected = empty_universe(16, 16);
+    expected.set_cells(&universe.live_cells().collect::<Vec<_>>());
+    expected.set_rule("B2/S");
+
+    // The next tick follows the universe's own rule for every cell, including
+    // the block that didn't change under B3/S23.
+    universe.tick();
+    expected.tick();
+    assert_eq!(universe.get_cells(), expected.get_cells());
+    assert!(!universe.get_cell(2, 2));
+}
+
+#[wasm_bindgen_test]
+pub fn test_edits_forget_colors() {
+    // A cell that is edited back to life is color 1 again.
+    let mut universe = empty_universe(8, 8);
+    universe.set_cell_color(2, 2, 2);
+    universe.clear();
+    universe.toggle_cell(2, 2);
+    assert_eq!(universe.cell_color(2, 2), 1);
+
+    universe.set_cell_color(2, 2, 2);
+    universe.set_cell(2, 2, false);
+    universe.set_cell(2, 2, true);
+    assert_eq!(universe.cell_color(2, 2), 1);
+
+    universe.set_cell_color(2, 2, 2);
+    universe.invert();
+    universe.invert();
+    assert_eq!(universe.cell_color(2, 2), 1);
+
+    // A cell that an ordinary tick kills and brings back is too, while a
+    // cell that survives keeps its color.
+    let mut universe = empty_universe(8, 8);
+    for (row, col) in [(2, 2), (2, 3), (2, 4)] {
+        universe.set_cell_color(row, col, 2);
+    }
+    universe.tick();
+    universe.tick();
+    assert_eq!(universe.cell_color(2, 2), 1);
+    assert_eq!(universe.cell_color(2, 3), 2);
+}
+
+#[wasm_bindgen_test]
+pub fn test_undo_restores_generation() {
+    let mut universe = empty_universe(16, 16);
+    universe.deploy("glider", 8, 8);
+    let cells = universe.get_cells().clone();
+
+    for _ in 0..3 {
+        universe.tick();
+    }
+    let ticked = universe.get_cells().clone();
+    for _ in 0..3 {
+        assert!(universe.undo());
+    }
+    assert_eq!(universe.get_cells(), &cells);
+    assert_eq!(universe.generation(), 0);
+
+    assert!(universe.redo());
+    assert_eq!(universe.generation(), 1);
+    assert!(universe.redo());
+    assert!(universe.redo());
+    assert_eq!(universe.get_cells(), &ticked);
+    assert_eq!(universe.generation(), 3);
+
+    universe.tick();
+    assert_eq!(universe.generation(), 4);
+
+    // Edits between ticks keep the generation they were made at.
+    universe.toggle_cell(0, 0);
+    assert!(universe.undo());
+    assert_eq!(universe.generation(), 4);
+    assert!(universe.redo());
+    assert_eq!(universe.generation(), 4);
+}
@@ -53,9 +53,7 @@ let animationId = null;
 function renderLoop() {
     // debugger;
 
-    for (let i = 0; i < +ticksPerFrame.value; ++i) {
-        universe.tick();
-    }
+    universe.tick_many(+ticksPerFrame.value);
 
     drawGrid();
     drawCells();